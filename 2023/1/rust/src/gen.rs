@@ -0,0 +1,89 @@
+//! Seeded calibration documents of any length, for `aoc gen`.
+
+use aoc::rand::Rng;
+
+/// Lines in a real input.
+pub const SIZE: usize = 1000;
+
+/// Letters that appear in no number word, so filler never spells a number by accident.
+const FILLER: &[u8] = b"abcdjklmpqy";
+
+const WORDS: [(&str, u8); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+/// Number words sharing a letter, with the first and the last number they spell.
+const OVERLAPS: [(&str, u8, u8); 8] = [
+    ("oneight", 1, 8),
+    ("twone", 2, 1),
+    ("threeight", 3, 8),
+    ("fiveight", 5, 8),
+    ("sevenine", 7, 9),
+    ("eightwo", 8, 2),
+    ("eighthree", 8, 3),
+    ("nineight", 9, 8),
+];
+
+/// A line of one to five numbers between filler, and its calibration value.
+pub fn calibration_line(rng: &mut Rng) -> (String, u8) {
+    let mut line = String::new();
+    let mut numbers = Vec::new();
+
+    push_filler(rng, &mut line, 0..4);
+    for i in 0..rng.range(1..6) {
+        if i > 0 {
+            push_filler(rng, &mut line, 1..4);
+        }
+
+        match rng.below(3) {
+            0 => {
+                let digit = rng.range(1..10) as u8;
+                line.push((b'0' + digit) as char);
+                numbers.push((digit, digit));
+            }
+            1 => {
+                let (word, value) = rng.pick(&WORDS);
+                line.push_str(word);
+                numbers.push((*value, *value));
+            }
+            _ => {
+                let (word, first, last) = rng.pick(&OVERLAPS);
+                line.push_str(word);
+                numbers.push((*first, *last));
+            }
+        }
+    }
+    push_filler(rng, &mut line, 0..4);
+
+    let first = numbers.first().unwrap().0;
+    let last = numbers.last().unwrap().1;
+    (line, first * 10 + last)
+}
+
+fn push_filler(rng: &mut Rng, line: &mut String, len: std::ops::Range<u64>) {
+    for _ in 0..rng.range(len) {
+        line.push(*rng.pick(FILLER) as char);
+    }
+}
+
+/// A calibration document of `size` lines.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| calibration_line(rng).0 + "\n").collect()
+}
+
+#[test]
+fn test_generate() {
+    let input = generate(&mut Rng::new(1), 50);
+
+    assert_eq!(input.lines().count(), 50);
+    assert_eq!(input, generate(&mut Rng::new(1), 50));
+    assert_ne!(input, generate(&mut Rng::new(2), 50));
+}
@@ -1,15 +1,54 @@
+mod gen;
+
+use aoc::args::Args;
+use aoc::rand::Rng;
+use aoc::report::Report;
+use std::io::IsTerminal;
 use std::iter::Peekable;
 use std::str::Chars;
 
 fn main() {
-    let total = std::fs::read_to_string("../input.txt")
-        .expect("Unable to read file")
-        .lines()
-        .filter_map(extract_digit_first_last)
-        .filter_map(combine_digits)
-        .fold(0, |acc, x| acc + x as u32);
+    aoc::log::init("day1");
+
+    let mut args = Args::from_env();
+    if args.subcommand().as_deref() == Some("gen") {
+        let mut rng = Rng::new(args.parse("--seed").unwrap_or(0));
+        print!(
+            "{}",
+            gen::generate(&mut rng, args.parse("--size").unwrap_or(gen::SIZE))
+        );
+        return;
+    }
+
+    let input = std::fs::read_to_string("../input.txt").expect("Unable to read file");
+
+    if args.flag("--explain") {
+        explain(&input);
+    }
+
+    // Digits and spelled out numbers both count, as in part 2
+    Report::from_env().part(2, "Total", || {
+        input
+            .lines()
+            .filter_map(extract_digit_first_last)
+            .filter_map(combine_digits)
+            .fold(0, |acc, x| acc + x as u32)
+    });
+}
 
-    println!("Total: {}", total);
+/// Print every line with its first and last number marked, followed by the calibration value.
+fn explain(input: &str) {
+    let colour = std::io::stdout().is_terminal();
+
+    for line in input.lines() {
+        match extract_spans_first_last(line) {
+            Some((first, last)) => {
+                let value = combine_digits((first.value, last.value)).unwrap();
+                println!("{} => {}", highlight(line, first, last, colour), value);
+            }
+            None => println!("{} => no digits", line),
+        }
+    }
 }
 
 const STR_NUMBER_MAP: [(&str, u8); 10] = [
@@ -25,6 +64,7 @@ const STR_NUMBER_MAP: [(&str, u8); 10] = [
     ("nine", 9),
 ];
 
+#[derive(Debug)]
 enum Match {
     None,
     Partial,
@@ -71,59 +111,138 @@ impl Matcher {
 }
 
 fn peek_str_number(iter: &mut Peekable<Chars>) -> Option<u8> {
-    println!("\npeek_str_number {:#?}", iter.clone().collect::<String>());
     let mut peekahead = iter.clone();
 
     let mut state = Matcher::new();
     while let Some(ch) = peekahead.peek() {
-        print!("\n+{}", ch);
-        match state.advance(*ch) {
-            Match::None => {
-                println!("!");
-                return None;
-            }
-            Match::Partial => {
-                print!("?{}", state.state.iter().filter(|x| x.is_some()).count());
-            }
-            Match::Full(num) => {
-                println!("={}", num);
-                return Some(num);
-            }
+        let matched = state.advance(*ch);
+        aoc::trace!(
+            "{:?} at {:?}: {:?}",
+            ch,
+            iter.clone().collect::<String>(),
+            matched
+        );
+
+        match matched {
+            Match::None => return None,
+            Match::Partial => {}
+            Match::Full(num) => return Some(num),
         }
         peekahead.next();
     }
     None
 }
 
-fn extract_digit_first_last(text: &str) -> Option<(u8, u8)> {
-    let mut first: Option<u8> = None;
-    let mut last: Option<u8> = None;
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpanKind {
+    Word,
+    Digit,
+}
+
+/// A number found in a line, covering the chars `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Span {
+    start: usize,
+    end: usize,
+    kind: SpanKind,
+    value: u8,
+}
+
+fn span_at(iter: &mut Peekable<Chars>, start: usize) -> Option<Span> {
+    let ch = iter.peek()?;
+    if let Some(di) = ch.to_digit(10) {
+        Some(Span {
+            start,
+            end: start + 1,
+            kind: SpanKind::Digit,
+            value: di as u8,
+        })
+    } else {
+        peek_str_number(iter).map(|num| Span {
+            start,
+            end: start + STR_NUMBER_MAP[num as usize].0.len(),
+            kind: SpanKind::Word,
+            value: num,
+        })
+    }
+}
+
+/// Like `extract_digit_first_last`, but keeps where each number was found.
+fn extract_spans_first_last(text: &str) -> Option<(Span, Span)> {
+    let mut first: Option<Span> = None;
+    let mut last: Option<Span> = None;
 
     let mut iter = text.chars().peekable();
+    let mut offset = 0;
 
-    while let Some(ch) = iter.peek() {
-        if let Some(di) = ch.to_digit(10) {
-            first = Some(di as u8);
-            last = Some(di as u8);
-            break;
-        } else if let Some(num) = peek_str_number(&mut iter) {
-            first = Some(num);
-            last = Some(num);
+    while iter.peek().is_some() {
+        if let Some(span) = span_at(&mut iter, offset) {
+            first = Some(span);
+            last = Some(span);
             break;
         }
         iter.next();
+        offset += 1;
     }
 
-    while let Some(ch) = iter.peek() {
-        if let Some(di) = ch.to_digit(10) {
-            last = Some(di as u8);
-        } else if let Some(num) = peek_str_number(&mut iter) {
-            last = Some(num);
+    while iter.peek().is_some() {
+        if let Some(span) = span_at(&mut iter, offset) {
+            last = Some(span);
         }
         iter.next();
+        offset += 1;
     }
 
-    matches!(first, Some(_)).then(|| (first.unwrap(), last.unwrap()))
+    first.zip(last)
+}
+
+fn extract_digit_first_last(text: &str) -> Option<(u8, u8)> {
+    extract_spans_first_last(text).map(|(first, last)| (first.value, last.value))
+}
+
+/// Marks the first and last span of a line, with ANSI colours or with `[..]` and `{..}`.
+fn highlight(text: &str, first: Span, last: Span, colour: bool) -> String {
+    const FIRST: &str = "\x1b[1;32m";
+    const LAST: &str = "\x1b[1;36m";
+    const BOTH: &str = "\x1b[1;35m";
+    const RESET: &str = "\x1b[0m";
+
+    let same = first == last;
+    let mut out = String::new();
+
+    for (i, ch) in text.chars().enumerate() {
+        let in_first = (first.start..first.end).contains(&i);
+        let in_last = (last.start..last.end).contains(&i);
+
+        if colour {
+            match (in_first, in_last) {
+                (true, true) if !same => out.push_str(BOTH),
+                (true, _) => out.push_str(FIRST),
+                (false, true) => out.push_str(LAST),
+                (false, false) => {}
+            }
+            out.push(ch);
+            if in_first || in_last {
+                out.push_str(RESET);
+            }
+        } else {
+            if i == first.start {
+                out.push('[');
+            }
+            if i == last.start && !same {
+                out.push('{');
+            }
+            out.push(ch);
+            if i + 1 == last.end && !same {
+                out.push('}');
+            }
+            if i + 1 == first.end {
+                out.push(']');
+            }
+        }
+    }
+
+    out
 }
 
 /// Second digit must be in range 0..=9
@@ -201,7 +320,6 @@ fn test_peek() {
     }
 
     fn parse_str(text: &str) -> Option<u8> {
-        println!("parse_str: {:#?}", text);
         let mut iter = text.chars().peekable();
         while iter.peek().is_some() {
             if let Some(d) = peek_str_number(&mut iter) {
@@ -213,3 +331,66 @@ fn test_peek() {
         None
     }
 }
+
+#[test]
+fn test_spans() {
+    let (first, last) = extract_spans_first_last("xtwone3four").unwrap();
+    assert_eq!(
+        first,
+        Span {
+            start: 1,
+            end: 4,
+            kind: SpanKind::Word,
+            value: 2
+        }
+    );
+    assert_eq!(
+        last,
+        Span {
+            start: 7,
+            end: 11,
+            kind: SpanKind::Word,
+            value: 4
+        }
+    );
+
+    let (first, last) = extract_spans_first_last("pqr3stu8vwx").unwrap();
+    assert_eq!(
+        (first.start, first.end, first.kind),
+        (3, 4, SpanKind::Digit)
+    );
+    assert_eq!((last.start, last.end, last.kind), (7, 8, SpanKind::Digit));
+
+    assert_eq!(extract_spans_first_last("abcdef"), None);
+}
+
+#[test]
+fn test_highlight() {
+    #[rustfmt::skip]
+    let expected = [
+        ("two1nine",     "[two]1{nine}"),
+        ("treb7uchet",   "treb[7]uchet"),
+        ("eightwothree", "[eight]wo{three}"),
+        ("zoneight",     "z[on{e]ight}"),
+    ];
+
+    for (input, expected) in expected {
+        let (first, last) = extract_spans_first_last(input).unwrap();
+        assert_eq!(
+            highlight(input, first, last, false),
+            expected,
+            "Input: {:#?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_generated_lines() {
+    for seed in 0..1000 {
+        let (line, expected) = gen::calibration_line(&mut Rng::new(seed));
+        let combined = extract_digit_first_last(&line).and_then(combine_digits);
+
+        assert_eq!(combined, Some(expected), "Seed {}: {:?}", seed, line);
+    }
+}
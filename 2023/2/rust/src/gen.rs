@@ -0,0 +1,71 @@
+//! Seeded game records of any length, for `aoc gen`.
+
+use crate::{Game, Set};
+use aoc::rand::Rng;
+
+/// Games in a real input.
+pub const SIZE: usize = 100;
+
+/// Most cubes of one colour shown at once.
+const MAX_CUBES: u64 = 20;
+
+/// `size` games of one to six sets, each showing one to three colours.
+pub fn games(rng: &mut Rng, size: usize) -> Vec<Game> {
+    (1..=size as u32)
+        .map(|id| {
+            let sets = (0..rng.range(1..7))
+                .map(|_| {
+                    let mut counts = [0; 3];
+                    let mut colours = [0, 1, 2];
+                    rng.shuffle(&mut colours);
+                    for &colour in &colours[..rng.range(1..4) as usize] {
+                        counts[colour] = rng.range(1..MAX_CUBES + 1) as u32;
+                    }
+                    Set::new(counts[0], counts[1], counts[2])
+                })
+                .collect();
+            Game::new(id, sets)
+        })
+        .collect()
+}
+
+/// A game as a line of the input, with the colours of each set in random order.
+fn format_game(rng: &mut Rng, game: &Game) -> String {
+    let sets = game
+        .sets
+        .iter()
+        .map(|set| {
+            let mut cubes = [(set.red, "red"), (set.green, "green"), (set.blue, "blue")]
+                .into_iter()
+                .filter(|(count, _)| *count > 0)
+                .map(|(count, colour)| format!("{} {}", count, colour))
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut cubes);
+            cubes.join(", ")
+        })
+        .collect::<Vec<_>>();
+
+    format!("Game {}: {}", game.id, sets.join("; "))
+}
+
+/// A record of `size` games.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    games(rng, size)
+        .iter()
+        .map(|game| format_game(rng, game) + "\n")
+        .collect()
+}
+
+#[test]
+fn test_generate() {
+    let games = games(&mut Rng::new(5), 200);
+    let mut rng = Rng::new(6);
+    let input = games
+        .iter()
+        .map(|game| format_game(&mut rng, game) + "\n")
+        .collect::<String>();
+
+    assert_eq!(crate::parse_games(&input).unwrap(), games);
+    assert!(games.iter().all(|game| !game.sets.is_empty()));
+    assert_eq!(generate(&mut Rng::new(1), 10).lines().count(), 10);
+}
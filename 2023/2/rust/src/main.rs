@@ -1,75 +1,64 @@
-use std::collections::binary_heap::PeekMut;
+mod gen;
 
-fn main() {
-    let games = std::fs::read_to_string("../input.txt")
-        .expect("Failed to read ../input.txt")
-        .lines()
-        .map(|line| {
-            let tokens = tokenize(line);
-            let mut tokens = tokens.iter().peekable();
-            let game = Game::from_tokens(&mut tokens);
-
-            game
-        })
-        .collect::<Vec<_>>();
-
-    let bag = Set::new(12, 13, 14);
-    let game_id_sum = games
-        .iter()
-        .filter_map(|game| {
-            if game.is_possible(&bag) {
-                Some(game.id)
-            } else {
-                None
-            }
-        })
-        .sum::<u32>();
+use aoc::args::Args;
+use aoc::lex::{self, Cursor, Lexer};
+use aoc::rand::Rng;
+use aoc::report::Report;
 
-    println!("Game ID sum: {}", game_id_sum);
+fn main() {
+    aoc::log::init("day2");
+
+    let mut args = Args::from_env();
+    if args.subcommand().as_deref() == Some("gen") {
+        let mut rng = Rng::new(args.parse("--seed").unwrap_or(0));
+        print!(
+            "{}",
+            gen::generate(&mut rng, args.parse("--size").unwrap_or(gen::SIZE))
+        );
+        return;
+    }
 
-    let game_power_sum = games.iter().map(|game| game.power()).sum::<u32>();
+    let input = std::fs::read_to_string("../input.txt").expect("Failed to read ../input.txt");
+    let mut report = Report::from_env();
+    let games = report.parse(|| parse_games(&input).unwrap_or_else(|err| panic!("{}", err)));
+
+    report.part(1, "Game ID sum", || {
+        let bag = Set::new(12, 13, 14);
+        games
+            .iter()
+            .filter_map(|game| {
+                if game.is_possible(&bag) {
+                    Some(game.id)
+                } else {
+                    None
+                }
+            })
+            .sum::<u32>()
+    });
 
-    println!("Game power sum: {}", game_power_sum);
+    report.part(2, "Game power sum", || {
+        games.iter().map(|game| game.power()).sum::<u32>()
+    });
 }
 
-#[derive(Debug, PartialEq)]
-enum Token {
-    Text(String),
-    Number(u32),
-    Semicolon,
+/// Games are separated by newlines and sets by semicolons; `:` and `,` carry no meaning.
+fn lexer() -> Lexer {
+    Lexer::new()
+        .separators(&[':', ','])
+        .punctuation(&[';', '\n'])
 }
 
-fn tokenize(input: &str) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut chars = input.chars().peekable();
+fn parse_games(input: &str) -> Result<Vec<Game>, lex::Error> {
+    let mut tokens = lexer().cursor(input)?;
+    let mut games = Vec::new();
 
-    while let Some(c) = chars.next() {
-        match c {
-            '0'..='9' => {
-                let mut number = c.to_digit(10).unwrap();
-                while let Some('0'..='9') = chars.peek() {
-                    number = number * 10 + chars.next().unwrap().to_digit(10).unwrap();
-                }
-                tokens.push(Token::Number(number));
-            }
-            'a'..='z' | 'A'..='Z' => {
-                let mut text = c.to_string();
-                while let Some('a'..='z' | 'A'..='Z') = chars.peek() {
-                    text.push(chars.next().unwrap());
-                }
-                tokens.push(Token::Text(text));
-            }
-            ';' => tokens.push(Token::Semicolon),
-            ' ' | ':' | ',' => {
-                // Ignored characters
-            }
-            _ => {
-                panic!("Unexpected character: {}", c);
-            }
+    while !tokens.is_at_end() {
+        if !tokens.eat_punct('\n') {
+            games.push(Game::from_tokens(&mut tokens)?);
         }
     }
 
-    tokens
+    Ok(games)
 }
 
 #[derive(Debug, PartialEq)]
@@ -83,27 +72,17 @@ impl Game {
         Self { id, sets }
     }
 
-    fn from_tokens<'a>(tokens: &mut std::iter::Peekable<impl Iterator<Item = &'a Token>>) -> Self {
-        match tokens.next().unwrap() {
-            Token::Text(text) if text == "Game" => {}
-            _ => panic!("Expected text 'Game'"),
-        }
-        let id = match tokens.next().unwrap() {
-            Token::Number(n) => *n,
-            _ => panic!("Expected number"),
-        };
+    /// Parse a game up to and including the end of its line.
+    fn from_tokens(tokens: &mut Cursor) -> Result<Self, lex::Error> {
+        tokens.expect_keyword("Game")?;
+        let id = tokens.expect_number()?;
 
         let mut sets = Vec::new();
-
-        while let Some(token) = tokens.peek() {
-            match token {
-                Token::Number(_) => sets.push(Set::from_tokens(tokens)),
-                Token::Semicolon => break,
-                _ => panic!("Unexpected token: {:?}", token),
-            }
+        while !tokens.is_at_end() && !tokens.eat_punct('\n') {
+            sets.push(Set::from_tokens(tokens)?);
         }
 
-        Self::new(id, sets)
+        Ok(Self::new(id, sets))
     }
 
     fn power(&self) -> u32 {
@@ -122,7 +101,7 @@ impl Game {
     }
 
     fn is_possible(&self, bag: &Set) -> bool {
-        self.sets.iter().all(|set| set.is_less_than(&bag))
+        self.sets.iter().all(|set| set.is_less_than(bag))
     }
 }
 
@@ -138,31 +117,26 @@ impl Set {
         Self { red, green, blue }
     }
 
-    fn from_tokens<'a>(tokens: &mut std::iter::Peekable<impl Iterator<Item = &'a Token>>) -> Self {
+    /// Parse a set up to and including its semicolon, or up to the end of the line.
+    fn from_tokens(tokens: &mut Cursor) -> Result<Self, lex::Error> {
         let mut red = 0;
         let mut green = 0;
         let mut blue = 0;
 
-        while let Some(token) = tokens.next() {
-            match token {
-                Token::Number(n) => {
-                    let color = tokens.next().unwrap();
-                    match color {
-                        Token::Text(color) => match color.as_str() {
-                            "red" => red = *n,
-                            "green" => green = *n,
-                            "blue" => blue = *n,
-                            _ => panic!("Unexpected color: {}", color),
-                        },
-                        _ => panic!("Unexpected token: {:?}", color),
-                    }
-                }
-                Token::Semicolon => break,
-                _ => panic!("Unexpected token: {:?}", token),
+        while tokens.peek().is_some_and(|token| !token.is_punct('\n')) {
+            if tokens.eat_punct(';') {
+                break;
+            }
+
+            let n = tokens.expect_number()?;
+            match tokens.expect_any_keyword(&["red", "green", "blue"])? {
+                "red" => red = n,
+                "green" => green = n,
+                _ => blue = n,
             }
         }
 
-        Self::new(red, green, blue)
+        Ok(Self::new(red, green, blue))
     }
 
     fn is_less_than(&self, other: &Self) -> bool {
@@ -184,9 +158,7 @@ fn test_example_input() {
     let bag = Set::new(12, 13, 14);
 
     for (expected, input) in expected {
-        let tokens = tokenize(input);
-        let mut tokens = tokens.iter().peekable();
-        let game = Game::from_tokens(&mut tokens);
+        let game = parse_game(input);
         assert_eq!(game.is_possible(&bag), expected);
     }
 }
@@ -202,63 +174,86 @@ fn test_example_input_2() {
         (Set::new(6,3,2),   36,   "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"),
     ];
 
-    let bag = Set::new(12, 13, 14);
-
     for (expected, power, input) in expected {
-        let tokens = tokenize(input);
-        let mut tokens = tokens.iter().peekable();
-        let game = Game::from_tokens(&mut tokens);
+        let game = parse_game(input);
 
         assert_eq!(game.max(), expected);
         assert_eq!(game.power(), power);
     }
 }
 
+#[cfg(test)]
+fn parse_game(input: &str) -> Game {
+    Game::from_tokens(&mut lexer().cursor(input).unwrap()).unwrap()
+}
+
 #[test]
 fn test_tokenize_input() {
+    use aoc::lex::TokenKind::{Number, Punct, Word};
+
     let input = "Game 1: 1 blue, 3 red; 24 red, 60 green";
     let expected = [
-        Token::Text("Game".to_string()),
-        Token::Number(1),
-        Token::Number(1),
-        Token::Text("blue".to_string()),
-        Token::Number(3),
-        Token::Text("red".to_string()),
-        Token::Semicolon,
-        Token::Number(24),
-        Token::Text("red".to_string()),
-        Token::Number(60),
-        Token::Text("green".to_string()),
+        (Word, "Game"),
+        (Number, "1"),
+        (Number, "1"),
+        (Word, "blue"),
+        (Number, "3"),
+        (Word, "red"),
+        (Punct(';'), ";"),
+        (Number, "24"),
+        (Word, "red"),
+        (Number, "60"),
+        (Word, "green"),
     ];
 
-    assert_eq!(tokenize(input), expected);
+    let tokens = lexer().tokenize(input).unwrap();
+    let tokens = tokens
+        .iter()
+        .map(|token| (token.kind, token.text))
+        .collect::<Vec<_>>();
+    assert_eq!(tokens, expected);
 }
 
 #[test]
 fn test_tokenize_set() {
-    let input = [
-        Token::Number(1),
-        Token::Text("blue".to_string()),
-        Token::Number(3),
-        Token::Text("red".to_string()),
-    ];
     let expected = Set::new(3, 0, 1);
 
-    let mut p = input.iter().peekable();
-    assert_eq!(Set::from_tokens(&mut p), expected);
+    let mut tokens = lexer().cursor("1 blue, 3 red").unwrap();
+    assert_eq!(Set::from_tokens(&mut tokens), Ok(expected));
 }
 
+#[test]
 fn test_tokenize_game() {
-    let input = [
-        Token::Text("Game".to_string()),
-        Token::Number(1),
-        Token::Number(1),
-        Token::Text("blue".to_string()),
-        Token::Number(3),
-        Token::Text("red".to_string()),
-    ];
     let expected = Game::new(1, vec![Set::new(3, 0, 1)]);
 
-    let mut p = input.iter().peekable();
-    assert_eq!(Game::from_tokens(&mut p), expected);
+    let mut tokens = lexer().cursor("Game 1 1 blue 3 red").unwrap();
+    assert_eq!(Game::from_tokens(&mut tokens), Ok(expected));
+}
+
+#[test]
+fn test_parse_games() {
+    let input = "Game 1: 3 blue; 2 green\r\n\nGame 2: 1 red, 4 blue\n";
+    let games = parse_games(input).unwrap();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].sets, [Set::new(0, 0, 3), Set::new(0, 2, 0)]);
+    assert_eq!(games[1].sets, [Set::new(1, 0, 4)]);
+}
+
+#[test]
+fn test_parse_errors() {
+    let err = parse_games("Game 1: 3 blue\nGame 2: 4 purple").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 2, column 11: expected one of \"red\", \"green\", \"blue\" but found \"purple\""
+    );
+
+    let err = parse_games("Game 1: 3 blue; red").unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.expected.as_str()),
+        (1, 17, "a number")
+    );
+
+    let err = parse_games("Game 1: 3 blue!").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 15));
 }
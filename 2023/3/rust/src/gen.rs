@@ -0,0 +1,57 @@
+//! Seeded engine schematics of any size, for `aoc gen` and the differential tests.
+
+use aoc::rand::Rng;
+
+/// Width and height of a real input.
+pub const SIZE: usize = 140;
+
+/// Percentage of symbol cells, about that of a real input.
+pub const DENSITY: u64 = 6;
+
+const SYMBOLS: [char; 11] = ['*', '*', '*', '#', '+', '$', '=', '%', '-', '@', '/'];
+
+/// Random rectangular schematic; `density` in percent controls how many cells are symbols.
+pub fn random_schematic(rng: &mut Rng, width: usize, height: usize, density: u64) -> Vec<String> {
+    (0..height)
+        .map(|_| {
+            let mut line = String::new();
+            while line.len() < width {
+                if rng.chance(density, 100) {
+                    line.push(*rng.pick(&SYMBOLS));
+                } else if !line.ends_with(|c: char| c.is_ascii_digit()) && rng.chance(1, 3) {
+                    // Numbers are at most three digits and never run into each other
+                    let digits = rng.range(1..4) as usize;
+                    for _ in 0..digits.min(width - line.len()) {
+                        line.push((b'0' + rng.below(10) as u8) as char);
+                    }
+                } else {
+                    line.push('.');
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// A square schematic of `size` rows and columns.
+pub fn generate(rng: &mut Rng, size: usize, density: u64) -> String {
+    random_schematic(rng, size, size, density)
+        .into_iter()
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[test]
+fn test_generate() {
+    let input = generate(&mut Rng::new(4), 30, 10);
+
+    assert_eq!(input.lines().count(), 30);
+    assert!(input.lines().all(|line| line.len() == 30));
+    assert!(crate::Schematic::parse(&input).is_ok());
+
+    let symbols = input
+        .chars()
+        .filter(|c| !c.is_ascii_digit() && !".\n".contains(*c))
+        .count();
+    assert!((40..150).contains(&symbols), "Symbols: {}", symbols);
+}
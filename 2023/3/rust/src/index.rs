@@ -0,0 +1,148 @@
+use crate::Token;
+use aoc::grid::Grid;
+use std::ops::{Range, RangeInclusive};
+
+pub type SpanId = usize;
+
+/// A horizontal run of digits read as one number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberSpan {
+    pub value: u32,
+    pub row: isize,
+    pub cols: Range<isize>,
+}
+
+impl NumberSpan {
+    /// Columns of the span widened by one cell on either side.
+    pub fn surrounding_x(&self) -> RangeInclusive<isize> {
+        (self.cols.start - 1)..=self.cols.end
+    }
+
+    /// Rows of the span widened by one row above and below.
+    pub fn surrounding_y(&self) -> RangeInclusive<isize> {
+        (self.row - 1)..=(self.row + 1)
+    }
+}
+
+/// Every number in a schematic, found in a single pass, plus a lookup from cell to span.
+pub struct NumberIndex {
+    spans: Vec<NumberSpan>,
+    cells: Grid<Option<SpanId>>,
+}
+
+impl NumberIndex {
+    pub fn new(tokens: &Grid<Token>) -> Self {
+        let mut spans: Vec<NumberSpan> = Vec::new();
+        let mut cells = Grid::filled(tokens.width(), tokens.height(), None);
+
+        for ((x, y), token) in tokens.iter() {
+            let Token::Digit(d) = *token else {
+                continue;
+            };
+
+            // Extend the previous span when this digit directly follows it
+            let id = match spans.last_mut() {
+                Some(span) if span.row == y && span.cols.end == x => {
+                    span.value = span.value * 10 + d as u32;
+                    span.cols.end += 1;
+                    spans.len() - 1
+                }
+                _ => {
+                    spans.push(NumberSpan {
+                        value: d as u32,
+                        row: y,
+                        cols: x..x + 1,
+                    });
+                    spans.len() - 1
+                }
+            };
+
+            cells[(x, y)] = Some(id);
+        }
+
+        Self { spans, cells }
+    }
+
+    pub fn span(&self, id: SpanId) -> &NumberSpan {
+        &self.spans[id]
+    }
+
+    #[cfg(test)]
+    pub fn span_at(&self, x: isize, y: isize) -> Option<SpanId> {
+        self.cells.get(x, y).copied().flatten()
+    }
+
+    /// Spans on row `y`, from left to right.
+    pub fn in_row(&self, y: isize) -> &[NumberSpan] {
+        let start = self.spans.partition_point(|span| span.row < y);
+        let end = self.spans.partition_point(|span| span.row <= y);
+        &self.spans[start..end]
+    }
+
+    /// Distinct spans touching `(x, y)`, diagonals included.
+    pub fn around(&self, x: isize, y: isize) -> Vec<SpanId> {
+        let mut ids = Vec::new();
+        for (_, id) in self.cells.window((x - 1)..=(x + 1), (y - 1)..=(y + 1)) {
+            if let Some(id) = *id {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+fn index_of(lines: &[&str]) -> NumberIndex {
+    NumberIndex::new(&Grid::from_rows(
+        lines.iter().map(|line| crate::parse_tokens(line)).collect(),
+    ))
+}
+
+#[test]
+fn test_index_spans() {
+    let index = index_of(&["467..114..", "...*......", "..35..633."]);
+
+    #[rustfmt::skip]
+    let expected = [
+        (467, 0, 0..3),
+        (114, 0, 5..8),
+        (35,  2, 2..4),
+        (633, 2, 6..9),
+    ];
+
+    let spans = index
+        .spans
+        .iter()
+        .map(|span| (span.value, span.row, span.cols.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(spans, expected);
+}
+
+#[test]
+fn test_index_rows_do_not_join() {
+    // A number at the end of a row must not continue on the next row
+    let index = index_of(&["..12", "34.."]);
+
+    let values = index.spans.iter().map(|s| s.value).collect::<Vec<_>>();
+    assert_eq!(values, [12, 34]);
+}
+
+#[test]
+fn test_index_lookup() {
+    let index = index_of(&["467..114..", "...*......", "..35..633."]);
+
+    assert_eq!(index.span_at(1, 0), Some(0));
+    assert_eq!(index.span_at(3, 0), None);
+    assert_eq!(index.span_at(-1, 0), None);
+    assert_eq!(index.span_at(8, 2), Some(3));
+
+    assert_eq!(index.around(3, 1), [0, 2]);
+    assert_eq!(index.around(9, 1), [3]);
+    assert!(index.around(0, 4).is_empty());
+
+    let row = index.in_row(2).iter().map(|s| s.value).collect::<Vec<_>>();
+    assert_eq!(row, [35, 633]);
+    assert!(index.in_row(1).is_empty());
+}
@@ -1,98 +1,171 @@
-use std::collections::HashSet;
-use std::io::Write;
+mod gen;
+mod index;
+#[cfg(test)]
+mod oracle;
+mod render;
+mod stream;
+
+use aoc::args::Args;
+use aoc::grid::{Grid, GridError};
+use aoc::rand::Rng;
+use aoc::report::Report;
+use index::NumberIndex;
 use std::ops::RangeInclusive;
 
 fn main() {
-    let schematic = std::fs::read_to_string("../input.txt")
-        .expect("Failed to read ../input.txt")
-        .lines()
-        .map(parse_tokens)
-        .collect::<Vec<_>>();
+    aoc::log::init("day3");
 
-    let schematic = Schematic::new(schematic);
+    let mut args = Args::from_env();
+    let command = args.subcommand();
 
-    let mut sum_pn = 0;
-    for row in 0..schematic.height() {
-        sum_pn += sum_partnumbers(row, &schematic);
+    if command.as_deref() == Some("gen") {
+        let mut rng = Rng::new(args.parse("--seed").unwrap_or(0));
+        let size = args.parse("--size").unwrap_or(gen::SIZE);
+        let density = args.parse("--density").unwrap_or(gen::DENSITY);
+        print!("{}", gen::generate(&mut rng, size, density));
+        return;
     }
-    println!("Sum of part numbers: {}", sum_pn);
 
-    let mut sum_gr = 0;
-    for row in 0..schematic.height() {
-        sum_gr += sum_gearratios(row, &schematic);
+    let mut report = Report::from_env();
+    if args.flag("--stream") {
+        stream_input(&mut report);
+        return;
     }
-    println!("Sum of gear ratios: {}", sum_gr);
-}
 
-/// Sum all numbers that have an symbol in any neighbouring cell.
-fn sum_partnumbers(y: isize, schema: &Schematic) -> u32 {
-    let mut partnumber_sum = 0;
-
-    let mut x = 0isize;
-    while x < schema.width() {
-        let (number, digit_count) = match schema.number_at(x, y) {
-            Some(r) => r,
-            None => {
-                x += 1;
-                continue;
+    let input = std::fs::read_to_string("../input.txt").expect("Failed to read ../input.txt");
+    let ragged = args.flag("--ragged");
+    let schematic = report.parse(|| {
+        if ragged {
+            Schematic::parse_padded(&input)
+        } else {
+            Schematic::parse(&input).unwrap_or_else(|err| panic!("Invalid ../input.txt: {}", err))
+        }
+    });
+
+    if command.as_deref() == Some("render") {
+        match args.value("--html") {
+            Some(path) => {
+                std::fs::write(&path, render::render_html(&schematic))
+                    .unwrap_or_else(|_| panic!("Failed to write to {}", path));
+                println!("Wrote {}", path);
             }
-        };
+            None => print!("{}", render::render_ansi(&schematic)),
+        }
+        return;
+    }
 
-        let surrounding_x = (x - 1)..=(x + digit_count);
-        let surrounding_y = (y - 1)..=(y + 1);
+    report.part(1, "Sum of part numbers", || {
+        (0..schematic.height())
+            .map(|row| sum_partnumbers(row, &schematic))
+            .sum::<u32>()
+    });
 
-        if schema.has_symbol(surrounding_x, surrounding_y) {
-            partnumber_sum += number;
-            x += digit_count as isize + 1;
-        } else {
-            x += 1;
-        }
+    report.part(2, "Sum of gear ratios", || {
+        (0..schematic.height())
+            .map(|row| sum_gearratios(row, &schematic))
+            .sum::<u32>()
+    });
+
+    if args.flag("--symbols") {
+        print_symbol_summary(&schematic);
     }
+}
 
-    partnumber_sum
+/// Same answers as `main`, without holding the whole schematic in memory. Each part
+/// reads the input as it goes, so all of its time counts as solving.
+fn stream_input(report: &mut Report) {
+    report.part(1, "Sum of part numbers", || {
+        stream_sum(|sums| sums.part_numbers)
+    });
+    report.part(2, "Sum of gear ratios", || {
+        stream_sum(|sums| sums.gear_ratios)
+    });
 }
 
-/// Sum for each gear token the product of all neighbouring numbers; having atleast two numbers.
-fn sum_gearratios(y: isize, schema: &Schematic) -> u32 {
-    let mut power_sum = 0;
+/// Sum of `pick` over the rows of `../input.txt`, read one row at a time.
+fn stream_sum(pick: impl Fn(&stream::RowSums) -> u32) -> u32 {
+    let file = std::fs::File::open("../input.txt").expect("Failed to read ../input.txt");
 
-    let mut x = 0isize;
-    while x < schema.width() {
-        if !matches!(schema.get(x, y), Some(Token::Gear)) {
-            x += 1;
-            continue;
-        }
+    stream::RowStream::new(std::io::BufReader::new(file))
+        .map(|sums| pick(&sums.expect("Failed to read ../input.txt")))
+        .sum()
+}
 
-        let mut num_coords = HashSet::new();
-        for y in (y - 1)..=(y + 1) {
-            for x in (x - 1)..=(x + 1) {
-                if let Some(coord) = schema.start_of_number(x, y) {
-                    num_coords.insert(coord);
-                }
-            }
-        }
+/// A gear is a `*` with exactly two neighbouring numbers.
+const GEAR: (char, usize) = ('*', 2);
 
-        if num_coords.len() >= 2 {
-            let mut values = Vec::new();
-            for (x, y) in num_coords {
-                let (num, _) = schema.number_at(x, y).unwrap();
-                values.push(num);
-            }
+/// Sum all numbers that have an symbol in any neighbouring cell.
+fn sum_partnumbers(y: isize, schema: &Schematic) -> u32 {
+    schema
+        .numbers
+        .in_row(y)
+        .iter()
+        .filter(|span| schema.has_symbol(span.surrounding_x(), span.surrounding_y()))
+        .map(|span| span.value)
+        .sum()
+}
 
-            power_sum += values.iter().fold(1 as u32, |acc, x| acc * x);
-        }
+/// Sum for each gear token the product of its two neighbouring numbers.
+fn sum_gearratios(y: isize, schema: &Schematic) -> u32 {
+    sum_ratios(y, GEAR, schema)
+}
 
-        x += 1;
-    }
+/// Sum for each `symbol` on the row the product of its neighbouring numbers; having exactly `count` numbers.
+fn sum_ratios(y: isize, (symbol, count): (char, usize), schema: &Schematic) -> u32 {
+    schema
+        .symbols_on_row(y, symbol)
+        .filter(|(_, numbers)| numbers.len() == count)
+        .map(|(_, numbers)| numbers.iter().product::<u32>())
+        .sum()
+}
 
-    power_sum
+/// For each `symbol` the sum of its neighbouring numbers.
+fn adjacent_sums(symbol: char, schema: &Schematic) -> Vec<((isize, isize), u32)> {
+    schema
+        .symbols(symbol)
+        .map(|(pos, numbers)| (pos, numbers.iter().sum()))
+        .collect()
+}
+
+/// Every `symbol` having exactly `count` neighbouring numbers, with the product of those numbers.
+fn symbol_ratios(symbol: char, count: usize, schema: &Schematic) -> Vec<((isize, isize), u32)> {
+    schema
+        .symbols(symbol)
+        .filter(|(_, numbers)| numbers.len() == count)
+        .map(|(pos, numbers)| (pos, numbers.iter().product()))
+        .collect()
+}
+
+fn print_symbol_summary(schema: &Schematic) {
+    let mut symbols = schema
+        .tokens
+        .iter()
+        .filter_map(|(_, token)| match token {
+            Token::Symbol(c) => Some(*c),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    symbols.sort();
+    symbols.dedup();
+
+    for symbol in symbols {
+        let sums = adjacent_sums(symbol, schema);
+        let pairs = symbol_ratios(symbol, 2, schema);
+        println!(
+            "{}: count {}, sum of adjacent numbers {}, with two numbers {} (ratio sum {})",
+            symbol,
+            sums.len(),
+            sums.iter().map(|(_, sum)| sum).sum::<u32>(),
+            pairs.len(),
+            pairs.iter().map(|(_, ratio)| ratio).sum::<u32>(),
+        );
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Token {
     Empty,
-    Symbol,
-    Gear,
+    Symbol(char),
     Digit(u8),
 }
 
@@ -100,95 +173,85 @@ impl From<char> for Token {
     fn from(c: char) -> Self {
         match c {
             '.' => Token::Empty,
-            '#' | '+' | '$' | '=' | '%' | '-' | '@' | '/' | '\\' | '&' => Token::Symbol,
-            '*' => Token::Gear,
             '0'..='9' => Token::Digit(c.to_digit(10).unwrap() as u8),
-            _ => panic!("Invalid char: {}", c),
+            _ => Token::Symbol(c),
         }
     }
 }
 
 fn parse_tokens(line: &str) -> Vec<Token> {
-    line.chars().into_iter().map(Token::from).collect()
+    line.chars().map(Token::from).collect()
 }
 
 struct Schematic {
-    tokens: Vec<Vec<Token>>,
+    tokens: Grid<Token>,
+    numbers: NumberIndex,
 }
 
 impl Schematic {
     fn new(tokens: Vec<Vec<Token>>) -> Self {
-        Self { tokens }
+        Self::from_grid(Grid::from_rows(tokens))
+    }
+
+    fn from_grid(tokens: Grid<Token>) -> Self {
+        let numbers = NumberIndex::new(&tokens);
+        Self { tokens, numbers }
+    }
+
+    /// Parse a rectangular schematic; rows of differing length are an error.
+    fn parse(text: &str) -> Result<Self, GridError> {
+        Grid::try_parse(text).map(Self::from_grid)
+    }
+
+    /// Parse a schematic whose rows may differ in length. Short rows are padded with
+    /// `Token::Empty`, as if they ended in `.` up to the longest row.
+    fn parse_padded(text: &str) -> Self {
+        Self::from_grid(Grid::parse_padded(text, Token::Empty))
     }
 
     fn width(&self) -> isize {
-        self.tokens[0].len() as isize
+        self.tokens.width() as isize
     }
 
     fn height(&self) -> isize {
-        self.tokens.len() as isize
+        self.tokens.height() as isize
     }
 
     fn get(&self, x: isize, y: isize) -> Option<Token> {
-        let valid_x = 0..self.width();
-        let valid_y = 0..self.height();
-
-        if !valid_x.contains(&x) || !valid_y.contains(&y) {
-            return None;
-        }
-
-        return Some(self.tokens[y as usize][x as usize]);
+        self.tokens.get(x, y).copied()
     }
 
     fn has_symbol(&self, rx: RangeInclusive<isize>, ry: RangeInclusive<isize>) -> bool {
-        for y in ry {
-            for x in rx.clone() {
-                if let Some(Token::Symbol) = self.get(x, y) {
-                    return true;
-                }
-
-                if let Some(Token::Gear) = self.get(x, y) {
-                    return true;
-                }
-            }
-        }
-
-        false
+        self.tokens
+            .window(rx, ry)
+            .any(|(_, token)| matches!(token, Token::Symbol(_)))
     }
 
-    fn number_at(&self, x: isize, y: isize) -> Option<(u32, isize)> {
-        let mut count = 0;
-        let mut sum = 0;
-        loop {
-            match self.get(x + count, y) {
-                Some(Token::Digit(d)) => {
-                    count += 1;
-                    sum *= 10;
-                    sum += d as u32;
-                }
-                _ => break,
-            }
-        }
-
-        match count {
-            0 => None,
-            _ => Some((sum, count)),
-        }
+    /// Every `symbol` on row `y` with the values of its distinct neighbouring numbers.
+    fn symbols_on_row(
+        &self,
+        y: isize,
+        symbol: char,
+    ) -> impl Iterator<Item = (isize, Vec<u32>)> + '_ {
+        (0..self.width())
+            .filter(move |&x| matches!(self.get(x, y), Some(Token::Symbol(c)) if c == symbol))
+            .map(move |x| {
+                let numbers = self
+                    .numbers
+                    .around(x, y)
+                    .into_iter()
+                    .map(|id| self.numbers.span(id).value)
+                    .collect();
+                (x, numbers)
+            })
     }
 
-    fn start_of_number(&self, x: isize, y: isize) -> Option<(isize, isize)> {
-        let mut count = 0;
-        loop {
-            match self.get(x - count, y) {
-                Some(Token::Digit(_)) => count += 1,
-                _ => break,
-            }
-        }
-
-        match count {
-            0 => None,
-            _ => Some((x - count + 1, y)),
-        }
+    /// Every `symbol` in the schematic with the values of its distinct neighbouring numbers.
+    fn symbols(&self, symbol: char) -> impl Iterator<Item = ((isize, isize), Vec<u32>)> + '_ {
+        (0..self.height()).flat_map(move |y| {
+            self.symbols_on_row(y, symbol)
+                .map(move |(x, numbers)| ((x, y), numbers))
+        })
     }
 }
 
@@ -272,63 +335,111 @@ fn test_cases_i_might_have_screwed_up() {
     assert_eq!(sum_text("#1.2.3#"), 4);
 }
 
-fn write_debug_schematic(schematic: &Schematic) {
-    let mut output = std::fs::File::create("../output-debug.txt")
-        .expect("Failed to write to ../output-debug.txt");
-
-    for row in 0..schematic.height() {
-        let mut x = 0isize;
-        while x < schematic.width() {
-            match schematic.get(x, row) {
-                Some(Token::Empty) => {
-                    write!(output, " ").unwrap();
-                    x += 1;
-                    continue;
-                }
-
-                Some(Token::Symbol) => {
-                    write!(output, "X").unwrap();
-                    x += 1;
-                    continue;
-                }
-
-                None => {
-                    write!(output, "?").unwrap();
-                    x += 1;
-                    continue;
-                }
-
-                Some(Token::Gear) => {
-                    write!(output, "*").unwrap();
-                    x += 1;
-                    continue;
-                }
-
-                Some(Token::Digit(d)) => {
-                    let (number, digit_count) = match schematic.number_at(x, row) {
-                        Some(r) => r,
-                        None => {
-                            write!(output, ".").unwrap();
-                            x += 1;
-                            continue;
-                        }
-                    };
-
-                    let surrounding_x = (x - 1)..=(x + digit_count + 1);
-                    let surrounding_y = (row - 1)..=(row + 1);
-
-                    if schematic.has_symbol(surrounding_x, surrounding_y) {
-                        write!(output, "{}", number).unwrap();
-                        x += digit_count as isize;
-                    } else {
-                        for _ in 0..digit_count {
-                            write!(output, "-").unwrap();
-                        }
-                        x += digit_count as isize;
-                    }
-                }
-            }
-        }
-        writeln!(output).unwrap();
+#[test]
+fn test_any_char_is_a_symbol() {
+    let schematic = Schematic::new(vec![parse_tokens("1?2.3 4!5")]);
+
+    assert_eq!(sum_partnumbers(0, &schematic), 1 + 2 + 3 + 4 + 5);
+    assert_eq!(sum_ratios(0, ('?', 2), &schematic), 2);
+    assert_eq!(sum_ratios(0, ('!', 2), &schematic), 20);
+    assert_eq!(sum_ratios(0, (' ', 2), &schematic), 12);
+}
+
+#[test]
+fn test_symbol_queries() {
+    #[rustfmt::skip]
+    let lines = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ];
+
+    let schematic = Schematic::new(lines.iter().map(|line| parse_tokens(line)).collect());
+
+    assert_eq!(adjacent_sums('#', &schematic), [((6, 3), 633)]);
+    assert_eq!(adjacent_sums('+', &schematic), [((5, 5), 592)]);
+    assert_eq!(
+        adjacent_sums('*', &schematic),
+        [((3, 1), 467 + 35), ((3, 4), 617), ((5, 8), 755 + 598)]
+    );
+
+    assert_eq!(
+        symbol_ratios('*', 2, &schematic),
+        [((3, 1), 16345), ((5, 8), 451490)]
+    );
+    assert_eq!(symbol_ratios('*', 1, &schematic), [((3, 4), 617)]);
+    assert!(symbol_ratios('#', 2, &schematic).is_empty());
+}
+
+#[test]
+fn test_gear_needs_exactly_two_numbers() {
+    let schematic = Schematic::new(vec![
+        parse_tokens("1.2"),
+        parse_tokens(".*."),
+        parse_tokens("3.."),
+    ]);
+
+    assert_eq!(sum_gearratios(1, &schematic), 0);
+    assert_eq!(sum_ratios(1, ('*', 3), &schematic), 6);
+}
+
+#[test]
+fn test_parse_input_files() {
+    let example = "467..114..\n...*......\n..35..633.\n";
+
+    for text in [
+        example.to_string(),
+        example.replace('\n', "\r\n"),
+        format!("{}\n\n", example),
+        example.trim_end().to_string(),
+    ] {
+        let schematic = Schematic::parse(&text).unwrap();
+        assert_eq!(schematic.width(), 10, "Input: {:#?}", text);
+        assert_eq!(schematic.height(), 3, "Input: {:#?}", text);
+        assert_eq!(sum_partnumbers(0, &schematic), 467, "Input: {:#?}", text);
     }
 }
+
+#[test]
+fn test_parse_empty() {
+    for text in ["", "\n", "\r\n\r\n"] {
+        let schematic = Schematic::parse(text).unwrap();
+        assert_eq!(schematic.width(), 0);
+        assert_eq!(schematic.height(), 0);
+        assert_eq!(schematic.get(0, 0), None);
+        assert_eq!(sum_partnumbers(0, &schematic), 0);
+        assert_eq!(sum_gearratios(0, &schematic), 0);
+    }
+}
+
+#[test]
+fn test_parse_ragged() {
+    let text = "467..114..\n...*..\n..35..633.";
+
+    assert_eq!(
+        Schematic::parse(text).err(),
+        Some(GridError::Ragged {
+            line: 2,
+            expected: 10,
+            found: 6
+        })
+    );
+
+    let schematic = Schematic::parse_padded(text);
+    assert_eq!(schematic.width(), 10);
+    assert_eq!(schematic.get(9, 1), Some(Token::Empty));
+    assert_eq!(sum_gearratios(1, &schematic), 467 * 35);
+
+    // The longest row does not have to be the first
+    let schematic = Schematic::parse_padded("1*\n..*.5");
+    assert_eq!(schematic.width(), 5);
+    assert_eq!(sum_partnumbers(0, &schematic), 1);
+    assert_eq!(sum_partnumbers(1, &schematic), 0);
+}
@@ -0,0 +1,225 @@
+//! Deliberately naive reference answers, and a differential check of the real solution
+//! against them on random schematics.
+
+use crate::gen::random_schematic;
+use crate::{parse_tokens, sum_gearratios, sum_partnumbers, Schematic};
+use aoc::rand::Rng;
+
+/// A number as the reference sees it: value, row and the columns `start..end`.
+struct Number {
+    value: u32,
+    row: usize,
+    start: usize,
+    end: usize,
+}
+
+fn is_symbol(c: char) -> bool {
+    !c.is_ascii_digit() && c != '.'
+}
+
+fn numbers(lines: &[String]) -> Vec<Number> {
+    let mut numbers = Vec::new();
+
+    for (row, line) in lines.iter().enumerate() {
+        let chars = line.chars().collect::<Vec<_>>();
+        let mut x = 0;
+        while x < chars.len() {
+            if !chars[x].is_ascii_digit() {
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < chars.len() && chars[x].is_ascii_digit() {
+                x += 1;
+            }
+
+            let text = chars[start..x].iter().collect::<String>();
+            numbers.push(Number {
+                value: text.parse().unwrap(),
+                row,
+                start,
+                end: x,
+            });
+        }
+    }
+
+    numbers
+}
+
+/// Whether `(x, y)` lies in the box around `number`, one cell wider on every side.
+fn touches(number: &Number, x: usize, y: usize) -> bool {
+    number.row.abs_diff(y) <= 1 && number.start <= x + 1 && x <= number.end
+}
+
+fn cells(lines: &[String]) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(y, line)| line.chars().enumerate().map(move |(x, c)| (x, y, c)))
+}
+
+/// Per row, the sum of numbers with a symbol anywhere in their bounding box.
+pub fn reference_partnumbers(lines: &[String]) -> Vec<u32> {
+    let mut sums = vec![0; lines.len()];
+
+    for number in numbers(lines) {
+        if cells(lines).any(|(x, y, c)| is_symbol(c) && touches(&number, x, y)) {
+            sums[number.row] += number.value;
+        }
+    }
+
+    sums
+}
+
+/// Per row, the sum of products for every `*` touching exactly two numbers.
+pub fn reference_gearratios(lines: &[String]) -> Vec<u32> {
+    let numbers = numbers(lines);
+    let mut sums = vec![0; lines.len()];
+
+    for (x, y, c) in cells(lines) {
+        if c != '*' {
+            continue;
+        }
+
+        let touching = numbers
+            .iter()
+            .filter(|number| touches(number, x, y))
+            .collect::<Vec<_>>();
+
+        if touching.len() == 2 {
+            sums[y] += touching[0].value * touching[1].value;
+        }
+    }
+
+    sums
+}
+
+/// Differences between the solution and the reference, described per row.
+pub fn differences(lines: &[String]) -> Vec<String> {
+    let schematic = Schematic::new(lines.iter().map(|line| parse_tokens(line)).collect());
+    let part_numbers = reference_partnumbers(lines);
+    let gear_ratios = reference_gearratios(lines);
+
+    let mut differences = Vec::new();
+    for y in 0..lines.len() {
+        let actual = sum_partnumbers(y as isize, &schematic);
+        if actual != part_numbers[y] {
+            differences.push(format!(
+                "row {}: part numbers {} but expected {}",
+                y, actual, part_numbers[y]
+            ));
+        }
+
+        let actual = sum_gearratios(y as isize, &schematic);
+        if actual != gear_ratios[y] {
+            differences.push(format!(
+                "row {}: gear ratios {} but expected {}",
+                y, actual, gear_ratios[y]
+            ));
+        }
+    }
+    differences
+}
+
+/// Reduce a failing schematic while it keeps failing: drop rows, drop columns and blank
+/// out cells until none of those steps preserves the failure.
+pub fn shrink(lines: Vec<String>, fails: impl Fn(&[String]) -> bool) -> Vec<String> {
+    let mut current = lines;
+
+    loop {
+        let candidates = shrink_candidates(&current);
+        match candidates.into_iter().find(|candidate| fails(candidate)) {
+            Some(smaller) => current = smaller,
+            None => return current,
+        }
+    }
+}
+
+fn shrink_candidates(lines: &[String]) -> Vec<Vec<String>> {
+    let mut candidates = Vec::new();
+    let width = lines.first().map_or(0, |line| line.len());
+
+    for y in 0..lines.len() {
+        let mut without = lines.to_vec();
+        without.remove(y);
+        candidates.push(without);
+    }
+
+    for x in 0..width {
+        candidates.push(
+            lines
+                .iter()
+                .map(|line| {
+                    let mut line = line.clone();
+                    line.remove(x);
+                    line
+                })
+                .collect(),
+        );
+    }
+
+    for (y, line) in lines.iter().enumerate() {
+        for (x, c) in line.char_indices() {
+            if c != '.' {
+                let mut blanked = lines.to_vec();
+                blanked[y].replace_range(x..x + 1, ".");
+                candidates.push(blanked);
+            }
+        }
+    }
+
+    candidates
+}
+
+#[test]
+fn test_reference_example() {
+    #[rustfmt::skip]
+    let lines = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ].map(String::from);
+
+    assert_eq!(reference_partnumbers(&lines).iter().sum::<u32>(), 4361);
+    assert_eq!(reference_gearratios(&lines).iter().sum::<u32>(), 467835);
+    assert!(differences(&lines).is_empty());
+}
+
+#[test]
+fn test_shrink() {
+    let lines = ["1.#", "...", "22*"].map(String::from).to_vec();
+
+    // Pretend any schematic containing `#` fails
+    let shrunk = shrink(lines, |lines| lines.iter().any(|line| line.contains('#')));
+
+    assert_eq!(shrunk, ["#"]);
+}
+
+#[test]
+fn test_differential() {
+    for seed in 0..500 {
+        let mut rng = Rng::new(seed);
+        let width = rng.range(1..16) as usize;
+        let height = rng.range(1..8) as usize;
+        let density = rng.range(5..40);
+        let lines = random_schematic(&mut rng, width, height, density);
+
+        if !differences(&lines).is_empty() {
+            let minimal = shrink(lines, |lines| !differences(lines).is_empty());
+            panic!(
+                "Seed {} disagrees with the reference, minimal schematic:\n{}\n{}",
+                seed,
+                minimal.join("\n"),
+                differences(&minimal).join("\n")
+            );
+        }
+    }
+}
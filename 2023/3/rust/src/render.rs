@@ -0,0 +1,290 @@
+use crate::{Schematic, Token, GEAR};
+use aoc::grid::Grid;
+use std::fmt::Write;
+
+/// What a cell shows up as in a rendered schematic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Empty,
+    SymbolHalo,
+    GearHalo,
+    PartNumber,
+    Number,
+    Symbol,
+    Gear,
+}
+
+impl Class {
+    fn ansi(self) -> &'static str {
+        match self {
+            Class::Empty => "2",
+            Class::SymbolHalo => "2;100",
+            Class::GearHalo => "2;43",
+            Class::PartNumber => "1;32",
+            Class::Number => "31",
+            Class::Symbol => "1;35",
+            Class::Gear => "1;30;43",
+        }
+    }
+
+    fn css(self) -> &'static str {
+        match self {
+            Class::Empty => "empty",
+            Class::SymbolHalo => "halo",
+            Class::GearHalo => "gear-halo",
+            Class::PartNumber => "part",
+            Class::Number => "number",
+            Class::Symbol => "symbol",
+            Class::Gear => "gear",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Class::Empty => "empty",
+            Class::SymbolHalo => "next to a symbol",
+            Class::GearHalo => "next to a gear",
+            Class::PartNumber => "part number",
+            Class::Number => "not a part number",
+            Class::Symbol => "symbol",
+            Class::Gear => "gear",
+        }
+    }
+}
+
+const LEGEND: [Class; 6] = [
+    Class::PartNumber,
+    Class::Number,
+    Class::Symbol,
+    Class::Gear,
+    Class::SymbolHalo,
+    Class::GearHalo,
+];
+
+/// Classify every cell, using the same adjacency rules as the puzzle answers.
+pub fn classify(schema: &Schematic) -> Grid<Class> {
+    let mut classes = schema.tokens.map(|token| match token {
+        Token::Empty => Class::Empty,
+        Token::Symbol(_) => Class::Symbol,
+        Token::Digit(_) => Class::Number,
+    });
+
+    for y in 0..schema.height() {
+        for span in schema.numbers.in_row(y) {
+            if schema.has_symbol(span.surrounding_x(), span.surrounding_y()) {
+                for x in span.cols.clone() {
+                    classes[(x, y)] = Class::PartNumber;
+                }
+            }
+        }
+    }
+
+    let (gear, count) = GEAR;
+    for ((x, y), numbers) in schema.symbols(gear) {
+        if numbers.len() == count {
+            classes[(x, y)] = Class::Gear;
+        }
+    }
+
+    let symbols = classes
+        .iter()
+        .filter(|(_, class)| matches!(class, Class::Symbol | Class::Gear))
+        .map(|(pos, class)| (pos, *class))
+        .collect::<Vec<_>>();
+
+    for ((x, y), class) in symbols {
+        let halo = match class {
+            Class::Gear => Class::GearHalo,
+            _ => Class::SymbolHalo,
+        };
+
+        let around = classes
+            .neighbours8(x, y)
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        for pos in around {
+            let cell = &mut classes[pos];
+            // Gear halos win over plain symbol halos where they overlap
+            if *cell == Class::Empty || (*cell == Class::SymbolHalo && halo == Class::GearHalo) {
+                *cell = halo;
+            }
+        }
+    }
+
+    classes
+}
+
+fn display_char(token: &Token) -> char {
+    match token {
+        Token::Empty => '.',
+        Token::Symbol(c) => *c,
+        Token::Digit(d) => (b'0' + d) as char,
+    }
+}
+
+/// Runs of equally classified cells per row.
+fn runs<'a>(
+    schema: &'a Schematic,
+    classes: &'a Grid<Class>,
+) -> impl Iterator<Item = Vec<(Class, String)>> + 'a {
+    schema
+        .tokens
+        .rows()
+        .zip(classes.rows())
+        .map(|(tokens, classes)| {
+            let mut runs: Vec<(Class, String)> = Vec::new();
+            for (token, &class) in tokens.iter().zip(classes) {
+                match runs.last_mut() {
+                    Some((last, text)) if *last == class => text.push(display_char(token)),
+                    _ => runs.push((class, display_char(token).to_string())),
+                }
+            }
+            runs
+        })
+}
+
+/// Render for a terminal, with a legend underneath.
+pub fn render_ansi(schema: &Schematic) -> String {
+    let classes = classify(schema);
+    let mut out = String::new();
+
+    for row in runs(schema, &classes) {
+        for (class, text) in row {
+            write!(out, "\x1b[{}m{}\x1b[0m", class.ansi(), text).unwrap();
+        }
+        out.push('\n');
+    }
+
+    out.push('\n');
+    for class in LEGEND {
+        writeln!(
+            out,
+            "\x1b[{}m  \x1b[0m {}",
+            class.ansi(),
+            class.description()
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = "\
+body { background: #0f0f23; color: #cccccc; font-family: monospace; }
+pre { line-height: 1.2; }
+.empty { color: #555555; }
+.halo { color: #555555; background: #333340; }
+.gear-halo { color: #555555; background: #5c4d00; }
+.part { color: #00cc00; font-weight: bold; }
+.number { color: #cc3333; }
+.symbol { color: #cc66ff; font-weight: bold; }
+.gear { color: #0f0f23; background: #ffff66; font-weight: bold; }
+.legend span { display: inline-block; min-width: 1.5em; margin-right: 0.5em; }
+";
+
+/// Render as a standalone HTML page.
+pub fn render_html(schema: &Schematic) -> String {
+    let classes = classify(schema);
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>Day 3 schematic</title>\n");
+    writeln!(out, "<style>\n{}</style>", STYLE).unwrap();
+    out.push_str("</head>\n<body>\n<pre>\n");
+
+    for row in runs(schema, &classes) {
+        for (class, text) in row {
+            write!(
+                out,
+                "<span class=\"{}\">{}</span>",
+                class.css(),
+                escape_html(&text)
+            )
+            .unwrap();
+        }
+        out.push('\n');
+    }
+
+    out.push_str("</pre>\n<ul class=\"legend\">\n");
+    for class in LEGEND {
+        writeln!(
+            out,
+            "<li><span class=\"{}\">&nbsp;</span>{}</li>",
+            class.css(),
+            class.description()
+        )
+        .unwrap();
+    }
+    out.push_str("</ul>\n</body>\n</html>\n");
+
+    out
+}
+
+#[cfg(test)]
+fn example() -> Schematic {
+    #[rustfmt::skip]
+    let lines = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+    ];
+
+    Schematic::new(lines.iter().map(|line| crate::parse_tokens(line)).collect())
+}
+
+#[test]
+fn test_classify() {
+    let classes = classify(&example());
+
+    assert_eq!(classes[(0, 0)], Class::PartNumber);
+    assert_eq!(classes[(5, 0)], Class::Number);
+    assert_eq!(classes[(3, 1)], Class::Gear);
+    assert_eq!(classes[(4, 1)], Class::GearHalo);
+    assert_eq!(classes[(6, 3)], Class::Symbol);
+    assert_eq!(classes[(5, 3)], Class::SymbolHalo);
+    assert_eq!(classes[(6, 2)], Class::PartNumber);
+    assert_eq!(classes[(0, 1)], Class::Empty);
+
+    // A lone `*` next to a single number is a plain symbol
+    assert_eq!(classes[(3, 4)], Class::Symbol);
+    assert_eq!(classes[(4, 4)], Class::SymbolHalo);
+}
+
+#[test]
+fn test_render_ansi() {
+    let rendered = render_ansi(&example());
+    let first_line = rendered.lines().next().unwrap();
+
+    assert_eq!(
+        first_line,
+        "\x1b[1;32m467\x1b[0m\x1b[2;43m..\x1b[0m\x1b[31m114\x1b[0m\x1b[2m..\x1b[0m"
+    );
+}
+
+#[test]
+fn test_render_html() {
+    let schematic = Schematic::new(vec![crate::parse_tokens("1<&")]);
+    let rendered = render_html(&schematic);
+
+    assert!(rendered.starts_with("<!DOCTYPE html>"));
+    assert!(
+        rendered.contains("<span class=\"part\">1</span><span class=\"symbol\">&lt;&amp;</span>\n")
+    );
+    assert!(rendered.trim_end().ends_with("</html>"));
+}
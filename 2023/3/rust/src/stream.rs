@@ -0,0 +1,264 @@
+use crate::{parse_tokens, sum_gearratios, sum_partnumbers, Schematic, Token};
+use aoc::grid::GridError;
+use std::io::{BufRead, Lines};
+
+/// Answers for a single row of a schematic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowSums {
+    pub row: usize,
+    pub part_numbers: u32,
+    pub gear_ratios: u32,
+}
+
+/// Evaluates a schematic while reading it, one row at a time.
+///
+/// A row's answer only depends on the row itself and its direct neighbours, so only
+/// three rows are kept in a ring buffer no matter how long the input is. Input follows
+/// the same rules as `Schematic::parse`: rows must all be as long as the first one,
+/// `\r\n` endings are accepted and trailing blank lines are ignored. Nothing follows an
+/// error, since the rows around it can't be evaluated.
+pub struct RowStream<R> {
+    lines: Lines<R>,
+    ring: [Vec<Token>; 3],
+    loaded: usize,
+    next: usize,
+    eof: bool,
+    failed: bool,
+    /// Source lines read, blank ones included.
+    line: usize,
+    /// Length of the first line.
+    width: Option<usize>,
+    /// First of the blank lines seen but not yet known to be trailing.
+    blank: Option<usize>,
+}
+
+impl<R: BufRead> RowStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            ring: Default::default(),
+            loaded: 0,
+            next: 0,
+            eof: false,
+            failed: false,
+            line: 0,
+            width: None,
+            blank: None,
+        }
+    }
+
+    /// Read until the row below the next one to evaluate is in the ring.
+    fn fill(&mut self) -> std::io::Result<()> {
+        while !self.eof && self.loaded <= self.next + 1 {
+            let Some(line) = self.lines.next() else {
+                self.eof = true;
+                break;
+            };
+
+            let line = line?;
+            self.line += 1;
+            if line.is_empty() {
+                self.width.get_or_insert(0);
+                self.blank.get_or_insert(self.line);
+                continue;
+            }
+
+            let row = parse_tokens(&line);
+            let width = *self.width.get_or_insert(row.len());
+
+            // A blank line that turns out not to be trailing is a row too short, unless
+            // the rows are all meant to be blank
+            let ragged = match self.blank {
+                Some(blank) if width > 0 => Some((blank, 0)),
+                _ if row.len() != width => Some((self.line, row.len())),
+                _ => None,
+            };
+            if let Some((line, found)) = ragged {
+                let err = GridError::Ragged {
+                    line,
+                    expected: width,
+                    found,
+                };
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+            }
+
+            self.ring[self.loaded % 3] = row;
+            self.loaded += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for RowStream<R> {
+    type Item = std::io::Result<RowSums>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if let Err(err) = self.fill() {
+            self.failed = true;
+            return Some(Err(err));
+        }
+
+        if self.next >= self.loaded {
+            return None;
+        }
+
+        let first = self.next.saturating_sub(1);
+        let last = (self.next + 1).min(self.loaded - 1);
+        let window = (first..=last)
+            .map(|row| self.ring[row % 3].clone())
+            .collect();
+
+        let schematic = Schematic::new(window);
+        let y = (self.next - first) as isize;
+
+        let sums = RowSums {
+            row: self.next,
+            part_numbers: sum_partnumbers(y, &schematic),
+            gear_ratios: sum_gearratios(y, &schematic),
+        };
+
+        self.next += 1;
+        Some(Ok(sums))
+    }
+}
+
+#[cfg(test)]
+fn assert_matches_full(lines: &[String]) {
+    let text = lines.join("\n");
+    let schematic = Schematic::new(lines.iter().map(|line| parse_tokens(line)).collect());
+
+    let streamed = RowStream::new(text.as_bytes())
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+
+    assert_eq!(streamed.len(), lines.len());
+    for sums in streamed {
+        let y = sums.row as isize;
+        assert_eq!(
+            sums.part_numbers,
+            sum_partnumbers(y, &schematic),
+            "Row {} of:\n{}",
+            sums.row,
+            text
+        );
+        assert_eq!(
+            sums.gear_ratios,
+            sum_gearratios(y, &schematic),
+            "Row {} of:\n{}",
+            sums.row,
+            text
+        );
+    }
+}
+
+#[test]
+fn test_stream_example() {
+    #[rustfmt::skip]
+    let lines = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ].map(String::from);
+
+    assert_matches_full(&lines);
+
+    let text = lines.join("\n");
+    let totals = RowStream::new(text.as_bytes())
+        .map(Result::unwrap)
+        .fold((0, 0), |(pn, gr), sums| {
+            (pn + sums.part_numbers, gr + sums.gear_ratios)
+        });
+    assert_eq!(totals, (4361, 467835));
+}
+
+#[test]
+fn test_stream_short_inputs() {
+    assert_eq!(RowStream::new("".as_bytes()).count(), 0);
+
+    assert_matches_full(&["1*2".to_string()]);
+    assert_matches_full(&["1..".to_string(), "*.2".to_string()]);
+}
+
+#[test]
+fn test_stream_random() {
+    use aoc::rand::Rng;
+
+    for seed in 0..100 {
+        let mut rng = Rng::new(seed);
+        let height = rng.range(1..12) as usize;
+        let lines = crate::gen::random_schematic(&mut rng, 12, height, 20);
+        assert_matches_full(&lines);
+    }
+}
+
+#[test]
+fn test_stream_line_endings() {
+    let text = "467..114..\r\n...*......\r\n..35..633.\r\n\r\n\r\n";
+
+    let sums = RowStream::new(text.as_bytes())
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+
+    assert_eq!(sums.len(), 3);
+    assert_eq!(sums[0].part_numbers, 467);
+    assert_eq!(sums[1].gear_ratios, 467 * 35);
+}
+
+#[test]
+fn test_stream_ragged() {
+    for (text, line) in [
+        ("467..114..\n...*..\n..35..633.", 2),
+        ("467..114..\n...*......\n\n..35..633.", 3),
+    ] {
+        let err = RowStream::new(text.as_bytes())
+            .find_map(Result::err)
+            .expect("Ragged input must fail");
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let err = err.into_inner().unwrap().downcast::<GridError>().unwrap();
+        assert!(
+            matches!(*err, GridError::Ragged { line: l, .. } if l == line),
+            "{:?}",
+            err
+        );
+    }
+}
+
+#[test]
+fn test_stream_stops_after_error() {
+    let text = "467..114..\n...*......\n..35..\n..35..633.";
+    let mut stream = RowStream::new(text.as_bytes());
+
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_err());
+    assert!(stream.next().is_none());
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn test_stream_ragged_like_try_parse() {
+    for text in ["\nabc", "abc\n\nabc", "\n\nabc", "abc\n\n\nab", "ab\nabc\n"] {
+        let expected = aoc::grid::Grid::<Token>::try_parse(text).unwrap_err();
+
+        let err = RowStream::new(text.as_bytes())
+            .find_map(Result::err)
+            .expect("Ragged input must fail");
+        let err = err.into_inner().unwrap().downcast::<GridError>().unwrap();
+        assert_eq!(*err, expected, "Input: {:?}", text);
+    }
+
+    for text in ["", "\n\n", "abc\n\n"] {
+        assert!(aoc::grid::Grid::<Token>::try_parse(text).is_ok());
+        assert!(RowStream::new(text.as_bytes()).all(|sums| sums.is_ok()));
+    }
+}
@@ -0,0 +1,133 @@
+//! Seeded scratchcards of any count, for `aoc gen`.
+
+use aoc::rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/// Cards in a real input.
+pub const SIZE: usize = 200;
+
+const WINNING: usize = 10;
+const SCRATCHED: usize = 25;
+
+/// Card numbers are drawn from `1..NUMBERS`.
+const NUMBERS: u32 = 100;
+
+/// Relative weights of each match count, from zero matches up to one per winning number.
+///
+/// Parsed from a comma separated list, e.g. `4,2,1` for mostly losing cards and never
+/// more than two matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matches(Vec<u64>);
+
+impl Default for Matches {
+    /// Mostly cards with few matches, as in a real input, so the copies stay countable.
+    fn default() -> Self {
+        Self(vec![12, 4, 3, 2, 2, 1, 1, 1, 1, 1, 1])
+    }
+}
+
+impl FromStr for Matches {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = s
+            .split(',')
+            .map(|weight| weight.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("Invalid weight in {:?}: {}", s, err))?;
+
+        if weights.len() > WINNING + 1 {
+            return Err(format!(
+                "At most {} weights, one per match count",
+                WINNING + 1
+            ));
+        }
+        if weights.iter().sum::<u64>() == 0 {
+            return Err("Weights must not all be zero".to_string());
+        }
+        Ok(Self(weights))
+    }
+}
+
+impl fmt::Display for Matches {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let weights = self.0.iter().map(u64::to_string).collect::<Vec<_>>();
+        write!(f, "{}", weights.join(","))
+    }
+}
+
+impl Matches {
+    fn pick(&self, rng: &mut Rng) -> usize {
+        let mut roll = rng.below(self.0.iter().sum());
+        for (matches, &weight) in self.0.iter().enumerate() {
+            if roll < weight {
+                return matches;
+            }
+            roll -= weight;
+        }
+        unreachable!("Roll beyond the total weight")
+    }
+}
+
+/// A card line with `matches` of its scratched numbers among the winning ones.
+fn card(rng: &mut Rng, id: usize, matches: usize) -> String {
+    let mut numbers = (1..NUMBERS).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+
+    let (winning, losing) = numbers.split_at(WINNING);
+    let mut scratched = winning[..matches].to_vec();
+    scratched.extend(&losing[..SCRATCHED - matches]);
+    rng.shuffle(&mut scratched);
+
+    let column = |numbers: &[u32]| {
+        numbers
+            .iter()
+            .map(|number| format!("{:>2}", number))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!(
+        "Card {:>3}: {} | {}",
+        id,
+        column(winning),
+        column(&scratched)
+    )
+}
+
+/// `size` cards with match counts drawn from `matches`. Cards near the end get fewer
+/// matches, so no card wins copies of cards past the last one.
+pub fn generate(rng: &mut Rng, size: usize, matches: &Matches) -> String {
+    (1..=size)
+        .map(|id| {
+            let count = matches.pick(rng).min(size - id);
+            card(rng, id, count) + "\n"
+        })
+        .collect()
+}
+
+#[test]
+fn test_matches() {
+    assert_eq!("4, 2,1".parse(), Ok(Matches(vec![4, 2, 1])));
+    assert!("0,0".parse::<Matches>().is_err());
+    assert!("1,x".parse::<Matches>().is_err());
+    assert!("1,1,1,1,1,1,1,1,1,1,1,1".parse::<Matches>().is_err());
+    assert_eq!(
+        Matches::default().to_string().parse(),
+        Ok(Matches::default())
+    );
+}
+
+#[test]
+fn test_generate() {
+    let matches = "0,0,0,1".parse().unwrap();
+    let input = generate(&mut Rng::new(8), 50, &matches);
+    let (cards, warnings) = crate::parse_input(&input).unwrap();
+
+    assert!(warnings.is_empty());
+    assert_eq!(cards.len(), 50);
+    for (i, card) in cards.iter().enumerate() {
+        assert_eq!(card.id as usize, i + 1);
+        assert_eq!(card.match_count as usize, 3.min(49 - i));
+    }
+}
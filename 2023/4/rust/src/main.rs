@@ -1,111 +1,325 @@
-use std::collections::HashMap;
+mod gen;
+mod number_set;
+mod parse;
+mod scoring;
+mod trace;
+
+use aoc::args::Args;
+use aoc::rand::Rng;
+use aoc::report::Report;
+use number_set::NumberSet;
+use parse::parse_input;
+use scoring::{Doubling, ScoringRule, RULES};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
 
 fn main() {
-    let cards = std::fs::read_to_string("../input.txt")
-        .expect("Failed to read ../input.txt")
-        .lines()
-        .map(parse_input)
-        .collect::<Vec<_>>();
+    aoc::log::init("day4");
 
-    let sum_score = cards
-        .iter()
-        .map(|card| card.score())
-        .fold(0, |acc, score| acc + score);
-    println!("Sum of scores: {}", sum_score);
+    let mut args = Args::from_env();
+    if args.subcommand().as_deref() == Some("gen") {
+        let mut rng = Rng::new(args.parse("--seed").unwrap_or(0));
+        let size = args.parse("--size").unwrap_or(gen::SIZE);
+        let matches = args.parse("--matches").unwrap_or_default();
+        print!("{}", gen::generate(&mut rng, size, &matches));
+        return;
+    }
+
+    let policy = args.parse("--overflow").unwrap_or(OverflowPolicy::Error);
+
+    let input = std::fs::read_to_string("../input.txt").expect("Failed to read ../input.txt");
+    let mut report = Report::from_env();
+    let (cards, warnings) =
+        report.parse(|| parse_input(&input).unwrap_or_else(|err| panic!("{}", err)));
+    for warning in warnings {
+        aoc::warn!("{}", warning);
+    }
+
+    report.part(1, "Sum of scores", || {
+        cards
+            .iter()
+            .map(|card| card.score())
+            .fold(0, u64::saturating_add)
+    });
 
-    let count_winnings = eval_cards_stage2(cards).len();
-    println!("Count of winnings: {}", count_winnings);
+    if args.flag("--rules") {
+        print_rules(&cards);
+    }
+
+    // Only filled in when part 2 runs, which with `AOC_PART=1` it doesn't
+    let mut copies = None;
+    report.part(2, "Count of winnings", || {
+        let copies = copies
+            .insert(eval_cards_stage2(&cards, policy).unwrap_or_else(|err| panic!("{}", err)));
+        total_copies(copies).unwrap_or_else(|err| panic!("{}", err))
+    });
+
+    if args.flag("--trace") {
+        let traces =
+            trace::trace_cards_stage2(&cards, policy).unwrap_or_else(|err| panic!("{}", err));
+        print!("{}", trace::format_table(&traces));
+    }
+
+    if let Some(path) = args.value("--dot") {
+        let traces =
+            trace::trace_cards_stage2(&cards, policy).unwrap_or_else(|err| panic!("{}", err));
+        std::fs::write(&path, trace::format_dot(&traces))
+            .unwrap_or_else(|err| panic!("Failed to write to {}: {}", path, err));
+    }
+
+    #[cfg(feature = "simulate")]
+    if let Some(copies) = copies {
+        assert_eq!(
+            Ok(copies),
+            simulate_cards_stage2(&cards, policy),
+            "Simulation disagrees with the counted copies"
+        );
+    }
 }
 
 type CardId = u32;
+type CopyCount = u64;
 
 #[derive(Debug, Clone)]
-struct WinningNumbers(Vec<u8>);
+struct WinningNumbers(NumberSet);
 
 #[derive(Debug, Clone)]
-struct ScratchCard(Vec<u8>);
+struct ScratchCard(NumberSet);
 
 #[derive(Debug, Clone)]
-struct Card(CardId, WinningNumbers, ScratchCard, u32, u32);
+#[allow(dead_code)] // The numbers are only kept for `Debug` output
+struct Card {
+    id: CardId,
+    winning_numbers: WinningNumbers,
+    scratch_card: ScratchCard,
+    match_count: u32,
+}
 
 impl Card {
     fn new(id: CardId, winning_numbers: WinningNumbers, scratch_card: ScratchCard) -> Self {
-        let score = {
-            // First number is worth 1 point, after that accumulate points by doubling
-            winning_numbers
-                .0
-                .iter()
-                .filter(|number| scratch_card.0.contains(number))
-                .fold(0, |acc, _| (acc * 2).max(1))
-        };
+        let match_count = winning_numbers.0.intersection_count(&scratch_card.0);
 
-        let match_count = {
-            winning_numbers
-                .0
-                .iter()
-                .filter(|n| scratch_card.0.contains(n))
-                .count() as u32
-        };
+        Self {
+            id,
+            winning_numbers,
+            scratch_card,
+            match_count,
+        }
+    }
 
-        Self(id, winning_numbers, scratch_card, score, match_count)
+    /// Score by the part 1 rule.
+    fn score(&self) -> u64 {
+        self.score_by(&Doubling)
     }
 
-    fn score(&self) -> u32 {
-        self.3
+    fn score_by(&self, rule: &dyn ScoringRule) -> u64 {
+        rule.score(self.match_count)
     }
 
     fn match_count(&self) -> u32 {
-        self.4
+        self.match_count
     }
 }
 
-fn parse_input(input: &str) -> Card {
-    // Example input: "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
-    //                     id  winning numbers| card numbers
-    let Some((id, numbers)) = input.split_once(": ") else {
-        panic!("Invalid winning numbers input");
-    };
+fn sum_scores(cards: &[Card], rule: &dyn ScoringRule) -> u64 {
+    cards
+        .iter()
+        .map(|card| card.score_by(rule))
+        .fold(0, u64::saturating_add)
+}
 
-    let Some((_, id)) = id.split_once(' ') else {
-        panic!("Invalid card id");
-    };
+/// Sum of scores for every built-in rule, side by side.
+fn print_rules(cards: &[Card]) {
+    println!("{:<12} {:>14}", "Rule", "Sum of scores");
+    for rule in RULES {
+        println!("{:<12} {:>14}", rule.name(), sum_scores(cards, rule));
+    }
+}
 
-    let Some((winningnumbers, cardnumbers)) = numbers.split_once(" | ") else {
-        panic!("Invalid input");
-    };
+/// What to do when a card wins more cards than there are after it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverflowPolicy {
+    /// Only the cards up to the end of the table are won.
+    Clamp,
+    /// Evaluation fails.
+    Error,
+    /// Counting continues from the first card, as if the table were a circle.
+    Wrap,
+}
 
-    let id = id.trim().parse().expect("Invalid card id");
+impl FromStr for OverflowPolicy {
+    type Err = String;
 
-    let winningnumbers = winningnumbers
-        .split_ascii_whitespace()
-        .map(|n| n.parse().expect("Invalid winning number"))
-        .collect::<Vec<u8>>();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(OverflowPolicy::Clamp),
+            "error" => Ok(OverflowPolicy::Error),
+            "wrap" => Ok(OverflowPolicy::Wrap),
+            _ => Err(format!("Expected clamp, error or wrap but got: {:#?}", s)),
+        }
+    }
+}
 
-    let cardnumbers = cardnumbers
-        .split_ascii_whitespace()
-        .map(|n| n.parse().expect("Invalid card number"))
-        .collect::<Vec<u8>>();
+#[derive(Debug, Clone, PartialEq)]
+enum EvalError {
+    DuplicateId(CardId),
+    /// The card wins `wins` cards but only `available` come after it.
+    OutOfRange {
+        id: CardId,
+        wins: u32,
+        available: usize,
+    },
+    /// Wrapping around lets this card win copies of itself, so copies never stop.
+    Cycle(CardId),
+    /// The copies of this card, or the total up to it, don't fit in a `CopyCount`.
+    CopyOverflow {
+        id: CardId,
+    },
+}
 
-    Card::new(id, WinningNumbers(winningnumbers), ScratchCard(cardnumbers))
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::DuplicateId(id) => write!(f, "Card {} appears more than once", id),
+            EvalError::OutOfRange {
+                id,
+                wins,
+                available,
+            } => write!(
+                f,
+                "Card {} wins {} cards but only {} follow it",
+                id, wins, available
+            ),
+            EvalError::Cycle(id) => write!(f, "Card {} keeps winning copies of itself", id),
+            EvalError::CopyOverflow { id } => {
+                write!(f, "Card {} takes the copies past {}", id, CopyCount::MAX)
+            }
+        }
+    }
 }
 
 /// Each card's match count determines how many copies of the cards it gets after.
-/// This repeats until no new cards are won.
-fn eval_cards_stage2(cards: Vec<Card>) -> Vec<CardId> {
-    let index: HashMap<CardId, Card> =
-        HashMap::from_iter(cards.into_iter().map(|card| (card.0, card)));
-    let mut won_ids = Vec::from_iter(index.keys().cloned());
-
-    let mut pending_ids = won_ids.clone();
-    while let Some(current_id) = pending_ids.pop() {
-        let match_count = index[&current_id].match_count();
-        for card_id in current_id + 1..=current_id + match_count {
-            won_ids.push(card_id);
-            pending_ids.push(card_id);
+/// "After" means the next cards by id; ids don't need to be contiguous or sorted.
+///
+/// Every copy of a card adds one copy to each card it wins, so once all cards that win a
+/// card are settled, that card is settled too. Walking the cards in that order counts
+/// every copy without playing them out.
+fn eval_cards_stage2(
+    cards: &[Card],
+    policy: OverflowPolicy,
+) -> Result<Vec<(CardId, CopyCount)>, EvalError> {
+    let cards = sorted_cards(cards)?;
+    let copies = cascade(&cards, policy, |_, _, _| {})?;
+
+    Ok(cards.iter().map(|card| card.id).zip(copies).collect())
+}
+
+/// Copies of all cards together.
+fn total_copies(copies: &[(CardId, CopyCount)]) -> Result<CopyCount, EvalError> {
+    copies.iter().try_fold(0, |total: CopyCount, &(id, count)| {
+        total
+            .checked_add(count)
+            .ok_or(EvalError::CopyOverflow { id })
+    })
+}
+
+/// Cards in id order; ids must be unique.
+fn sorted_cards(cards: &[Card]) -> Result<Vec<&Card>, EvalError> {
+    let mut cards = cards.iter().collect::<Vec<_>>();
+    cards.sort_by_key(|card| card.id);
+
+    if let Some(pair) = cards.windows(2).find(|pair| pair[0].id == pair[1].id) {
+        return Err(EvalError::DuplicateId(pair[0].id));
+    }
+
+    Ok(cards)
+}
+
+/// Copies of each of the sorted `cards`. Every step of the cascade is reported to
+/// `contribute` as (winner index, won index, copies added).
+fn cascade(
+    cards: &[&Card],
+    policy: OverflowPolicy,
+    mut contribute: impl FnMut(usize, usize, CopyCount),
+) -> Result<Vec<CopyCount>, EvalError> {
+    let len = cards.len();
+    let wins = (0..len)
+        .map(|i| won_by(cards, i, policy))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Settle cards once every card winning them is settled
+    let mut pending_winners = vec![0; len];
+    for won in wins.iter().flatten() {
+        pending_winners[*won] += 1;
+    }
+
+    let mut settled = (0..len)
+        .filter(|&i| pending_winners[i] == 0)
+        .collect::<VecDeque<_>>();
+    let mut copies: Vec<CopyCount> = vec![1; len];
+    let mut settled_count = 0;
+
+    while let Some(i) = settled.pop_front() {
+        settled_count += 1;
+        for &won in &wins[i] {
+            copies[won] = copies[won]
+                .checked_add(copies[i])
+                .ok_or(EvalError::CopyOverflow { id: cards[won].id })?;
+            contribute(i, won, copies[i]);
+            pending_winners[won] -= 1;
+            if pending_winners[won] == 0 {
+                settled.push_back(won);
+            }
         }
     }
 
-    won_ids
+    if settled_count < len {
+        let stuck = (0..len).find(|&i| pending_winners[i] > 0).unwrap();
+        return Err(EvalError::Cycle(cards[stuck].id));
+    }
+
+    Ok(copies)
+}
+
+/// Indices of the sorted `cards` won by card `i`, following `policy` past the end.
+fn won_by(cards: &[&Card], i: usize, policy: OverflowPolicy) -> Result<Vec<usize>, EvalError> {
+    let len = cards.len();
+    let count = cards[i].match_count() as usize;
+    let available = len - i - 1;
+
+    match policy {
+        _ if count <= available => Ok((i + 1..=i + count).collect()),
+        OverflowPolicy::Clamp => Ok((i + 1..len).collect()),
+        OverflowPolicy::Error => Err(EvalError::OutOfRange {
+            id: cards[i].id,
+            wins: cards[i].match_count(),
+            available,
+        }),
+        OverflowPolicy::Wrap => Ok((i + 1..=i + count).map(|j| j % len).collect()),
+    }
+}
+
+/// Plays out every single won card, which takes as long as there are cards in the end.
+/// Never finishes when wrapping makes a cycle, so only run it where `cascade` succeeded.
+#[cfg(feature = "simulate")]
+fn simulate_cards_stage2(
+    cards: &[Card],
+    policy: OverflowPolicy,
+) -> Result<Vec<(CardId, CopyCount)>, EvalError> {
+    let cards = sorted_cards(cards)?;
+    let wins = (0..cards.len())
+        .map(|i| won_by(&cards, i, policy))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut copies: Vec<CopyCount> = vec![0; cards.len()];
+    let mut pending = (0..cards.len()).collect::<Vec<_>>();
+    while let Some(current) = pending.pop() {
+        copies[current] += 1;
+        pending.extend(&wins[current]);
+    }
+
+    Ok(cards.iter().map(|card| card.id).zip(copies).collect())
 }
 
 #[test]
@@ -121,7 +335,7 @@ fn test_example_input_1() {
     ];
 
     for (expected, input) in expected {
-        let card = parse_input(input);
+        let card = card_of(input);
         assert_eq!(expected, card.score(), "Input: {}", input);
     }
 }
@@ -138,21 +352,213 @@ fn test_stage_2() {
         (6, 1,  "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
     ];
 
-    let won_ids = eval_cards_stage2(
-        expected
-            .iter()
-            .map(|(_, _, input)| parse_input(input))
-            .collect::<Vec<_>>(),
-    );
+    let cards = cards_of(&expected.map(|(_, _, input)| input));
+    let copies = eval_cards_stage2(&cards, OverflowPolicy::Error).unwrap();
 
-    for (id, count, input) in expected {
-        assert_eq!(
-            count,
-            won_ids.iter().filter(|&&card| card == id).count(),
-            "Input: {}",
-            input
-        );
+    for ((id, count, input), copies) in expected.iter().zip(&copies) {
+        assert_eq!((*id, *count), *copies, "Input: {}", input);
     }
 
-    assert_eq!(30, won_ids.len());
+    assert_eq!(30, copies.iter().map(|(_, count)| count).sum::<CopyCount>());
+
+    #[cfg(feature = "simulate")]
+    assert_eq!(
+        Ok(copies),
+        simulate_cards_stage2(&cards, OverflowPolicy::Error)
+    );
+}
+
+#[test]
+fn test_stage_2_counts_without_simulating() {
+    // Every card wins the next two, so the copies grow like the Fibonacci numbers
+    let count = 60;
+    let cards = (1..=count)
+        .map(|id| match id {
+            _ if id + 2 <= count => format!("Card {}: 1 2 | 1 2", id),
+            _ => format!("Card {}: 1 2 | 3 4", id),
+        })
+        .collect::<Vec<_>>();
+    let cards = parse_input(&cards.join("\n")).unwrap().0;
+
+    let copies = eval_cards_stage2(&cards, OverflowPolicy::Error).unwrap();
+
+    assert_eq!(copies[0], (1, 1));
+    assert_eq!(copies[1], (2, 2));
+    assert_eq!(copies[2], (3, 4));
+    assert_eq!(copies[3], (4, 7));
+    assert_eq!(copies[59], (60, 1548008755920));
+}
+
+#[test]
+fn test_stage_2_copy_overflow() {
+    // The copies grow like the Fibonacci numbers, which pass a u64 before card 100
+    let count = 100;
+    let cards = (1..=count)
+        .map(|id| match id {
+            _ if id + 2 <= count => format!("Card {}: 1 2 | 1 2", id),
+            _ => format!("Card {}: 1 2 | 3 4", id),
+        })
+        .collect::<Vec<_>>();
+    let cards = parse_input(&cards.join("\n")).unwrap().0;
+
+    let err = eval_cards_stage2(&cards, OverflowPolicy::Error).unwrap_err();
+    assert!(
+        matches!(err, EvalError::CopyOverflow { id } if id < count),
+        "{:?}",
+        err
+    );
+    assert!(trace::trace_cards_stage2(&cards, OverflowPolicy::Error).is_err());
+
+    // Each count fits, but together they don't
+    let copies = [(1, CopyCount::MAX - 1), (2, 1), (3, 1)];
+    assert_eq!(total_copies(&copies[..2]), Ok(CopyCount::MAX));
+    assert_eq!(
+        total_copies(&copies),
+        Err(EvalError::CopyOverflow { id: 3 })
+    );
+}
+
+#[cfg(test)]
+fn cards_of(lines: &[&str]) -> Vec<Card> {
+    parse_input(&lines.join("\n")).unwrap().0
+}
+
+#[cfg(test)]
+fn card_of(line: &str) -> Card {
+    cards_of(&[line]).remove(0)
+}
+
+#[test]
+fn test_stage_2_unsorted_ids() {
+    // Card 4 does not exist; card 3 still wins the next card, which is card 5
+    let cards = cards_of(&[
+        "Card 5: 1 | 2",
+        "Card 1: 1 2 | 1 2",
+        "Card 3: 1 | 1",
+        "Card 2: 1 | 3",
+    ]);
+
+    let copies = eval_cards_stage2(&cards, OverflowPolicy::Error).unwrap();
+    assert_eq!(copies, [(1, 1), (2, 2), (3, 2), (5, 3)]);
+
+    #[cfg(feature = "simulate")]
+    assert_eq!(
+        Ok(copies),
+        simulate_cards_stage2(&cards, OverflowPolicy::Error)
+    );
+}
+
+#[test]
+fn test_stage_2_duplicate_ids() {
+    let cards = cards_of(&["Card 1: 1 | 2", "Card 1: 1 | 2"]);
+
+    assert_eq!(
+        eval_cards_stage2(&cards, OverflowPolicy::Clamp),
+        Err(EvalError::DuplicateId(1))
+    );
+}
+
+#[test]
+fn test_stage_2_policy_clamp() {
+    let cards = cards_of(&["Card 1: 1 | 1", "Card 2: 1 2 3 | 1 2 3"]);
+
+    let copies = eval_cards_stage2(&cards, OverflowPolicy::Clamp).unwrap();
+    assert_eq!(copies, [(1, 1), (2, 2)]);
+
+    #[cfg(feature = "simulate")]
+    assert_eq!(
+        Ok(copies),
+        simulate_cards_stage2(&cards, OverflowPolicy::Clamp)
+    );
+}
+
+#[test]
+fn test_stage_2_policy_error() {
+    let cards = cards_of(&["Card 1: 1 | 1", "Card 2: 1 2 3 | 1 2 3"]);
+
+    assert_eq!(
+        eval_cards_stage2(&cards, OverflowPolicy::Error),
+        Err(EvalError::OutOfRange {
+            id: 2,
+            wins: 3,
+            available: 0
+        })
+    );
+}
+
+#[test]
+fn test_stage_2_policy_wrap() {
+    // Card 3 wins card 1, which in turn wins card 2
+    let cards = cards_of(&["Card 1: 1 | 1", "Card 2: 1 | 2", "Card 3: 1 | 1"]);
+
+    let copies = eval_cards_stage2(&cards, OverflowPolicy::Wrap).unwrap();
+    assert_eq!(copies, [(1, 2), (2, 3), (3, 1)]);
+
+    #[cfg(feature = "simulate")]
+    assert_eq!(
+        Ok(copies),
+        simulate_cards_stage2(&cards, OverflowPolicy::Wrap)
+    );
+
+    // Wrapping from card 2 back to card 1, which wins card 2 again
+    let cards = cards_of(&["Card 1: 1 | 1", "Card 2: 1 | 1"]);
+
+    assert_eq!(
+        eval_cards_stage2(&cards, OverflowPolicy::Wrap),
+        Err(EvalError::Cycle(1))
+    );
+}
+
+#[test]
+fn test_overflow_policy_from_str() {
+    assert_eq!("clamp".parse(), Ok(OverflowPolicy::Clamp));
+    assert_eq!("error".parse(), Ok(OverflowPolicy::Error));
+    assert_eq!("wrap".parse(), Ok(OverflowPolicy::Wrap));
+    assert!("skip".parse::<OverflowPolicy>().is_err());
+}
+
+#[test]
+fn test_numbers_above_255() {
+    let card = card_of("Card 1: 41 300 1000 17 | 1000 17 256 300");
+
+    assert_eq!(card.match_count(), 3);
+    assert_eq!(card.score(), 4);
+}
+
+#[test]
+fn test_more_than_64_matches() {
+    let numbers = (1..=100)
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let cards = cards_of(&[
+        &format!("Card 1: {} | {}", numbers, numbers),
+        "Card 2: 1 | 1",
+    ]);
+
+    assert_eq!(cards[0].match_count(), 100);
+    assert_eq!(cards[0].score(), u64::MAX);
+    assert_eq!(sum_scores(&cards, &Doubling), u64::MAX);
+}
+
+#[test]
+fn test_rules_over_example() {
+    let cards = cards_of(&[
+        "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+        "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+        "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+        "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    ]);
+
+    let sums = RULES.map(|rule| (rule.name(), sum_scores(&cards, rule)));
+    assert_eq!(
+        sums,
+        [
+            ("doubling", 13),
+            ("linear", 10 + 3 + 3 + 1),
+            ("match-count", 4 + 2 + 2 + 1)
+        ]
+    );
 }
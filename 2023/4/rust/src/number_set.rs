@@ -0,0 +1,82 @@
+pub type Number = u32;
+
+/// Numbers below this fit in the bitset.
+const BITS: Number = u128::BITS;
+
+/// A set of card numbers.
+///
+/// Puzzle numbers are all below 100, so they are kept as bits of a `u128`. A set holding
+/// any larger number falls back to a sorted list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberSet {
+    Bits(u128),
+    Sorted(Vec<Number>),
+}
+
+impl NumberSet {
+    pub fn contains(&self, number: Number) -> bool {
+        match self {
+            NumberSet::Bits(bits) => number < BITS && bits & (1 << number) != 0,
+            NumberSet::Sorted(numbers) => numbers.binary_search(&number).is_ok(),
+        }
+    }
+
+    /// How many numbers are in both sets.
+    pub fn intersection_count(&self, other: &NumberSet) -> u32 {
+        match (self, other) {
+            (NumberSet::Bits(a), NumberSet::Bits(b)) => (a & b).count_ones(),
+            (NumberSet::Sorted(numbers), set) | (set, NumberSet::Sorted(numbers)) => {
+                numbers.iter().filter(|&&n| set.contains(n)).count() as u32
+            }
+        }
+    }
+}
+
+impl FromIterator<Number> for NumberSet {
+    fn from_iter<I: IntoIterator<Item = Number>>(iter: I) -> Self {
+        let mut numbers = iter.into_iter().collect::<Vec<_>>();
+
+        if numbers.iter().all(|&n| n < BITS) {
+            NumberSet::Bits(numbers.iter().fold(0, |bits, n| bits | 1 << n))
+        } else {
+            numbers.sort_unstable();
+            numbers.dedup();
+            NumberSet::Sorted(numbers)
+        }
+    }
+}
+
+#[test]
+fn test_small_numbers_use_bits() {
+    let set = NumberSet::from_iter([41, 48, 83, 86, 17, 0, 127]);
+
+    assert!(matches!(set, NumberSet::Bits(_)));
+    assert!(set.contains(0));
+    assert!(set.contains(127));
+    assert!(!set.contains(1));
+    assert!(!set.contains(128));
+    assert!(!set.contains(1000));
+}
+
+#[test]
+fn test_large_numbers_use_sorted() {
+    let set = NumberSet::from_iter([300, 5, 128, 5]);
+
+    assert_eq!(set, NumberSet::Sorted(vec![5, 128, 300]));
+    assert!(set.contains(128));
+    assert!(!set.contains(127));
+}
+
+#[test]
+fn test_intersection_count() {
+    let small = NumberSet::from_iter([41, 48, 83, 86, 17]);
+    let other = NumberSet::from_iter([83, 86, 6, 31, 17, 9, 48, 53]);
+    let large = NumberSet::from_iter([17, 1000, 86, 4000]);
+    let larger = NumberSet::from_iter([86, 4000, 5000]);
+
+    assert_eq!(small.intersection_count(&other), 4);
+    assert_eq!(small.intersection_count(&large), 2);
+    assert_eq!(large.intersection_count(&small), 2);
+    assert_eq!(large.intersection_count(&larger), 2);
+    assert_eq!(NumberSet::from_iter([]).intersection_count(&small), 0);
+}
@@ -0,0 +1,227 @@
+//! Card lines, e.g. `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`.
+//!
+//! Whitespace around `:` and `|` is optional. Mistakes are reported with the line, column
+//! and token they were found at.
+
+use crate::number_set::{Number, NumberSet};
+use crate::{Card, ScratchCard, WinningNumbers};
+use aoc::lex::Error;
+use aoc::pattern::{Captures, Pattern};
+use std::fmt;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum List {
+    Winning,
+    Scratched,
+}
+
+/// Input that parses, but is probably not what was meant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    DuplicateNumber {
+        line: usize,
+        column: usize,
+        number: Number,
+        list: List,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::DuplicateNumber {
+                line,
+                column,
+                number,
+                list,
+            } => {
+                let list = match list {
+                    List::Winning => "winning numbers",
+                    List::Scratched => "scratched numbers",
+                };
+                write!(
+                    f,
+                    "Line {}, column {}: {} appears more than once in the {}",
+                    line, column, number, list
+                )
+            }
+        }
+    }
+}
+
+const CARD: &str = "Card {id:u32}: {winning:[u32]} | {scratched:[u32]}";
+
+/// `CARD`, compiled on first use.
+fn card_pattern() -> &'static Pattern {
+    static PATTERN: OnceLock<Pattern> = OnceLock::new();
+    PATTERN.get_or_init(|| Pattern::new(CARD))
+}
+
+/// Cards of every non-blank line in `text`, along with any warnings.
+pub fn parse_input(text: &str) -> Result<(Vec<Card>, Vec<Warning>), Error> {
+    let mut warnings = Vec::new();
+    let cards = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| parse_card(index + 1, line, &mut warnings))
+        .collect::<Result<_, _>>()?;
+
+    Ok((cards, warnings))
+}
+
+/// Parse a single card from `input`, which is line `line` of the input.
+pub fn parse_card(line: usize, input: &str, warnings: &mut Vec<Warning>) -> Result<Card, Error> {
+    let captures = card_pattern().parse_line(line, input)?;
+
+    Ok(Card::new(
+        captures.get("id"),
+        WinningNumbers(numbers(&captures, List::Winning, line, warnings)),
+        ScratchCard(numbers(&captures, List::Scratched, line, warnings)),
+    ))
+}
+
+/// The numbers in `list`, warning about any that appear more than once. Numbers are
+/// compared by value, so `05` and `5` are the same number.
+fn numbers(captures: &Captures, list: List, line: usize, warnings: &mut Vec<Warning>) -> NumberSet {
+    let name = match list {
+        List::Winning => "winning",
+        List::Scratched => "scratched",
+    };
+    let numbers = captures.list::<Number>(name);
+
+    for (i, field) in captures.fields(name).iter().enumerate() {
+        if numbers[..i].contains(&numbers[i]) {
+            warnings.push(Warning::DuplicateNumber {
+                line,
+                column: field.column,
+                number: numbers[i],
+                list,
+            });
+        }
+    }
+
+    numbers.into_iter().collect()
+}
+
+#[test]
+fn test_flexible_whitespace() {
+    let mut warnings = Vec::new();
+    let spaced = parse_card(1, "Card 1: 41 48 | 48 6", &mut warnings).unwrap();
+
+    for input in [
+        "Card 1:41 48|48 6",
+        "Card   1 :  41 48  |  48 6  ",
+        "\tCard 1\t:\t41\t48\t|\t48\t6",
+    ] {
+        let card = parse_card(1, input, &mut warnings).unwrap();
+        assert_eq!(
+            (card.id, card.match_count()),
+            (spaced.id, spaced.match_count()),
+            "Input: {:?}",
+            input
+        );
+    }
+
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_errors() {
+    #[rustfmt::skip]
+    let cases = [
+        ("Game 1: 1 | 2",     1,  Some("Game"), "\"Card\""),
+        ("Card x: 1 | 2",     6,  Some("x"),    "a number"),
+        ("Card 1 1 | 2",      8,  Some("1"),    "\":\""),
+        ("Card 1",            7,  None,         "\":\""),
+        ("Card 1: 1 2",       12, None,         "\"|\""),
+        ("Card 1: 1 a | 2",   11, Some("a"),    "a number or \"|\""),
+        ("Card 1: 1 | 2 |",   15, Some("|"),    "a number"),
+        ("Card 1: 1 | 2 : 3", 15, Some(":"),    "a number"),
+        ("Card 1: 1 | -2",    13, Some("-2"),   "a number that fits in u32"),
+    ];
+
+    for (input, column, found, expected) in cases {
+        let err = parse_card(3, input, &mut Vec::new()).unwrap_err();
+        assert_eq!(
+            err,
+            Error {
+                line: 3,
+                column,
+                expected: expected.to_string(),
+                found: found.map(str::to_string),
+            },
+            "Input: {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_error_display() {
+    let err = parse_card(2, "Card 1: 1 a | 2", &mut Vec::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 2, column 11: expected a number or \"|\" but found \"a\""
+    );
+
+    let err = parse_card(4, "Card 1", &mut Vec::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 4, column 7: expected \":\" but found end of input"
+    );
+}
+
+#[test]
+fn test_duplicate_numbers() {
+    let (cards, warnings) = parse_input("Card 1: 1 2 | 3\nCard 2: 5 6 5 | 7 6 7").unwrap();
+
+    assert_eq!(cards[1].match_count(), 1);
+    assert_eq!(
+        warnings,
+        [
+            Warning::DuplicateNumber {
+                line: 2,
+                column: 13,
+                number: 5,
+                list: List::Winning
+            },
+            Warning::DuplicateNumber {
+                line: 2,
+                column: 21,
+                number: 7,
+                list: List::Scratched
+            }
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "Line 2, column 21: 7 appears more than once in the scratched numbers"
+    );
+}
+
+#[test]
+fn test_duplicate_numbers_with_leading_zeros() {
+    let (cards, warnings) = parse_input("Card 1: 5 05 | 5").unwrap();
+
+    assert_eq!(cards[0].match_count(), 1);
+    assert_eq!(
+        warnings,
+        [Warning::DuplicateNumber {
+            line: 1,
+            column: 11,
+            number: 5,
+            list: List::Winning
+        }]
+    );
+}
+
+#[test]
+fn test_parse_input_lines() {
+    let text = "Card 1: 1 | 1\r\n\r\nCard 2: 1 | 2\nCard x: 1 | 2\n";
+    assert_eq!(parse_input(text).unwrap_err().line, 4);
+
+    let (cards, _) = parse_input("Card 1: 1 | 1\r\n\r\nCard 2: 1 | 2\n\n").unwrap();
+    assert_eq!(cards.len(), 2);
+}
@@ -0,0 +1,83 @@
+/// Points a card is worth, given how many of its numbers match.
+pub trait ScoringRule {
+    fn name(&self) -> &'static str;
+    fn score(&self, match_count: u32) -> u64;
+}
+
+/// First number is worth 1 point, after that accumulate points by doubling.
+pub struct Doubling;
+
+/// First number is worth 1 point, every next number one point more than the one before.
+pub struct Linear;
+
+/// Every matching number is worth 1 point.
+pub struct MatchCount;
+
+impl ScoringRule for Doubling {
+    fn name(&self) -> &'static str {
+        "doubling"
+    }
+
+    /// Saturates past 64 matches, which a `u64` can't double up to.
+    fn score(&self, match_count: u32) -> u64 {
+        match match_count {
+            0 => 0,
+            n => 1u64.checked_shl(n - 1).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+impl ScoringRule for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn score(&self, match_count: u32) -> u64 {
+        let n = match_count as u64;
+        n * (n + 1) / 2
+    }
+}
+
+impl ScoringRule for MatchCount {
+    fn name(&self) -> &'static str {
+        "match-count"
+    }
+
+    fn score(&self, match_count: u32) -> u64 {
+        match_count as u64
+    }
+}
+
+/// The built-in rules, part 1's rule first.
+pub const RULES: [&dyn ScoringRule; 3] = [&Doubling, &Linear, &MatchCount];
+
+#[test]
+fn test_rules() {
+    #[rustfmt::skip]
+    let expected = [
+        // matches, doubling, linear, match-count
+        (0, 0, 0,  0),
+        (1, 1, 1,  1),
+        (2, 2, 3,  2),
+        (3, 4, 6,  3),
+        (4, 8, 10, 4),
+    ];
+
+    for (matches, doubling, linear, match_count) in expected {
+        assert_eq!(Doubling.score(matches), doubling, "Matches: {}", matches);
+        assert_eq!(Linear.score(matches), linear, "Matches: {}", matches);
+        assert_eq!(
+            MatchCount.score(matches),
+            match_count,
+            "Matches: {}",
+            matches
+        );
+    }
+}
+
+#[test]
+fn test_doubling_saturates() {
+    assert_eq!(Doubling.score(64), 1 << 63);
+    assert_eq!(Doubling.score(65), u64::MAX);
+    assert_eq!(Doubling.score(u32::MAX), u64::MAX);
+}
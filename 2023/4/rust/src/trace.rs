@@ -0,0 +1,198 @@
+//! Per-card record of the stage 2 cascade, for debugging which cards won which copies.
+
+use crate::{cascade, sorted_cards, Card, CardId, CopyCount, EvalError, OverflowPolicy};
+use std::fmt::Write;
+
+/// How a single card ended up with its copies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardTrace {
+    pub id: CardId,
+    pub matches: u32,
+    pub copies: CopyCount,
+    /// Cards this one won copies of, in order.
+    pub won: Vec<CardId>,
+    /// Cards that won copies of this one, with how many copies each gave.
+    pub contributors: Vec<(CardId, CopyCount)>,
+}
+
+/// Same evaluation as `eval_cards_stage2`, but keeps every contribution.
+pub fn trace_cards_stage2(
+    cards: &[Card],
+    policy: OverflowPolicy,
+) -> Result<Vec<CardTrace>, EvalError> {
+    let cards = sorted_cards(cards)?;
+    let mut traces = cards
+        .iter()
+        .map(|card| CardTrace {
+            id: card.id,
+            matches: card.match_count(),
+            copies: 1,
+            won: Vec::new(),
+            contributors: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    let copies = cascade(&cards, policy, |winner, won, count| {
+        traces[winner].won.push(cards[won].id);
+        traces[won].contributors.push((cards[winner].id, count));
+    })?;
+
+    for (trace, copies) in traces.iter_mut().zip(copies) {
+        trace.copies = copies;
+    }
+
+    Ok(traces)
+}
+
+/// Ids as runs of consecutive ids, e.g. `2-4,6`; `-` when there are none.
+fn format_ids(ids: &[CardId]) -> String {
+    let mut runs: Vec<(CardId, CardId)> = Vec::new();
+    for &id in ids {
+        match runs.last_mut() {
+            Some((_, last)) if *last + 1 == id => *last = id,
+            _ => runs.push((id, id)),
+        }
+    }
+
+    if runs.is_empty() {
+        return "-".to_string();
+    }
+
+    runs.iter()
+        .map(|&(first, last)| {
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{}-{}", first, last)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn format_table(traces: &[CardTrace]) -> String {
+    let mut table = format!(
+        "{:>6} {:>7} {:>16}  {:<12} {}\n",
+        "Card", "Matches", "Copies", "Won", "From"
+    );
+
+    for trace in traces {
+        let from = trace
+            .contributors
+            .iter()
+            .map(|(id, count)| format!("{}x{}", id, count))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            table,
+            "{:>6} {:>7} {:>16}  {:<12} {}",
+            trace.id,
+            trace.matches,
+            trace.copies,
+            format_ids(&trace.won),
+            from
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+/// Graphviz graph of the cascade: a node per card and an edge per win, labelled with
+/// the copies it added.
+pub fn format_dot(traces: &[CardTrace]) -> String {
+    let mut dot = String::from("digraph cascade {\n    rankdir=LR;\n    node [shape=box];\n");
+
+    for trace in traces {
+        writeln!(
+            dot,
+            "    card{} [label=\"Card {}\\n{} matches\\n{} copies\"];",
+            trace.id, trace.id, trace.matches, trace.copies
+        )
+        .unwrap();
+    }
+
+    for trace in traces {
+        for (from, count) in &trace.contributors {
+            writeln!(
+                dot,
+                "    card{} -> card{} [label=\"{}\"];",
+                from, trace.id, count
+            )
+            .unwrap();
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+fn example_traces() -> Vec<CardTrace> {
+    let cards = crate::cards_of(&[
+        "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+        "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+        "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+        "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    ]);
+
+    trace_cards_stage2(&cards, OverflowPolicy::Error).unwrap()
+}
+
+#[test]
+fn test_trace_example() {
+    let traces = example_traces();
+
+    let copies = traces.iter().map(|t| (t.id, t.copies)).collect::<Vec<_>>();
+    assert_eq!(copies, [(1, 1), (2, 2), (3, 4), (4, 8), (5, 14), (6, 1)]);
+
+    assert_eq!(traces[0].won, [2, 3, 4, 5]);
+    assert!(traces[0].contributors.is_empty());
+    assert_eq!(traces[3].contributors, [(1, 1), (2, 2), (3, 4)]);
+    assert_eq!(traces[4].contributors, [(1, 1), (3, 4), (4, 8)]);
+    assert!(traces[5].contributors.is_empty());
+
+    // Every copy beyond the original comes from a contributor
+    for trace in &traces {
+        let given = trace.contributors.iter().map(|(_, n)| n).sum::<CopyCount>();
+        assert_eq!(trace.copies, 1 + given, "Card {}", trace.id);
+    }
+}
+
+#[test]
+fn test_format_ids() {
+    assert_eq!(format_ids(&[]), "-");
+    assert_eq!(format_ids(&[2, 3, 4, 5]), "2-5");
+    assert_eq!(format_ids(&[5, 1, 2]), "5,1-2");
+}
+
+#[test]
+fn test_format_table() {
+    let table = format_table(&example_traces());
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 7);
+    assert!(lines[0].trim_start().starts_with("Card"));
+    assert_eq!(
+        lines[4].split_whitespace().collect::<Vec<_>>(),
+        ["4", "1", "8", "5", "1x1", "2x2", "3x4"]
+    );
+    assert_eq!(
+        lines[6].split_whitespace().collect::<Vec<_>>(),
+        ["6", "0", "1", "-"]
+    );
+}
+
+#[test]
+fn test_format_dot() {
+    let dot = format_dot(&example_traces());
+
+    assert!(dot.starts_with("digraph cascade {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("card4 [label=\"Card 4\\n1 matches\\n8 copies\"];"));
+    assert!(dot.contains("card3 -> card4 [label=\"4\"];"));
+    assert_eq!(dot.matches("->").count(), 9);
+}
@@ -0,0 +1,153 @@
+//! Seeded almanacs of any size, for `aoc gen`.
+
+use aoc::rand::Rng;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Rows per map in a real input.
+pub const SIZE: usize = 40;
+
+/// Values in a real input stay below this.
+pub const SPAN: u64 = 1 << 32;
+
+/// The solution adds values up as `i64`, so any two of them have to fit in one.
+const MAX_SPAN: u64 = 1 << 62;
+
+/// Seed ranges, as `start length` pairs on the seeds line.
+const SEED_RANGES: usize = 10;
+
+const MAPS: [&str; 7] = [
+    "seed-to-soil",
+    "soil-to-fertilizer",
+    "fertilizer-to-water",
+    "water-to-light",
+    "light-to-temperature",
+    "temperature-to-humidity",
+    "humidity-to-location",
+];
+
+/// `count` ranges of random length that don't overlap, in order, within `0..span`.
+fn disjoint_ranges(rng: &mut Rng, count: usize, span: u64) -> Vec<Range<u64>> {
+    assert!(
+        span > count as u64,
+        "Span {} too small for {} ranges",
+        span,
+        count
+    );
+
+    let mut cuts = BTreeSet::new();
+    while cuts.len() < count + 1 {
+        cuts.insert(rng.below(span + 1));
+    }
+
+    let cuts = cuts.into_iter().collect::<Vec<_>>();
+    cuts.windows(2).map(|pair| pair[0]..pair[1]).collect()
+}
+
+/// Map rows as `destination source length`. The sources are consecutive ranges, and the
+/// destinations the same ranges in another order, so neither overlap.
+fn map_rows(rng: &mut Rng, rows: usize, span: u64) -> Vec<[u64; 3]> {
+    let sources = disjoint_ranges(rng, rows, span);
+    let Some(start) = sources.first().map(|range| range.start) else {
+        return Vec::new();
+    };
+
+    let mut order = sources.clone();
+    rng.shuffle(&mut order);
+
+    let mut destination = start;
+    let mut rows = Vec::new();
+    for source in order {
+        let length = source.end - source.start;
+        rows.push([destination, source.start, length]);
+        destination += length;
+    }
+
+    rng.shuffle(&mut rows);
+    rows
+}
+
+/// Why `generate` can't make an almanac with these options, if it can't.
+pub fn check(rows: usize, span: u64) -> Result<(), String> {
+    // Every range needs at least one value of its own
+    let min = rows.max(SEED_RANGES * 2) as u64 + 1;
+    if (min..=MAX_SPAN).contains(&span) {
+        Ok(())
+    } else {
+        Err(format!(
+            "--span must be between {} and {} for --size {}",
+            min, MAX_SPAN, rows
+        ))
+    }
+}
+
+/// An almanac with `rows` rows per map and every value below `span`, which must `check`.
+pub fn generate(rng: &mut Rng, rows: usize, span: u64) -> String {
+    let mut seeds = disjoint_ranges(rng, SEED_RANGES * 2, span)
+        .chunks_exact(2)
+        .map(|pair| pair[0].clone())
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut seeds);
+
+    let seeds = seeds
+        .iter()
+        .map(|range| format!("{} {}", range.start, range.end - range.start))
+        .collect::<Vec<_>>();
+    let mut almanac = format!("seeds: {}\n", seeds.join(" "));
+
+    for name in MAPS {
+        almanac.push_str(&format!("\n{} map:\n", name));
+        for [destination, source, length] in map_rows(rng, rows, span) {
+            almanac.push_str(&format!("{} {} {}\n", destination, source, length));
+        }
+    }
+
+    almanac
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(check(SIZE, SPAN), Ok(()));
+    assert_eq!(check(0, 21), Ok(()));
+    assert_eq!(check(100, 101), Ok(()));
+    assert_eq!(check(1, MAX_SPAN), Ok(()));
+
+    assert!(check(0, 20).is_err());
+    assert!(check(100, 100).is_err());
+    assert!(check(1, MAX_SPAN + 1).is_err());
+    assert!(check(1, u64::MAX).is_err());
+
+    for (rows, span) in [(0, 21), (100, 101)] {
+        assert_eq!(
+            generate(&mut Rng::new(1), rows, span).lines().count(),
+            8 + 7 * (rows + 1)
+        );
+    }
+}
+
+#[test]
+fn test_map_rows() {
+    for seed in 0..100 {
+        let rows = map_rows(&mut Rng::new(seed), 8, 100);
+        assert_eq!(rows.len(), 8);
+
+        let sources = rows
+            .iter()
+            .map(|[_, source, length]| *source..source + length);
+        let destinations = rows
+            .iter()
+            .map(|[destination, _, length]| *destination..destination + length);
+        for ranges in [sources.collect::<Vec<_>>(), destinations.collect()] {
+            let mut ranges = ranges;
+            ranges.sort_by_key(|range| range.start);
+            assert!(
+                ranges.windows(2).all(|pair| pair[0].end <= pair[1].start),
+                "Seed {}",
+                seed
+            );
+            assert!(ranges
+                .iter()
+                .all(|range| range.start < range.end && range.end <= 100));
+        }
+    }
+}
@@ -1,32 +1,53 @@
+mod gen;
+
+use aoc::args::Args;
+use aoc::interval::IntervalSet;
+use aoc::lex;
+use aoc::pattern::Pattern;
+use aoc::rand::Rng;
+use aoc::report::Report;
 use std::cell::Cell;
 
 fn main() {
+    aoc::log::init("day5");
+
+    let mut args = Args::from_env();
+    if args.subcommand().as_deref() == Some("gen") {
+        let mut rng = Rng::new(args.parse("--seed").unwrap_or(0));
+        let rows = args.parse("--size").unwrap_or(gen::SIZE);
+        let span = args.parse("--span").unwrap_or(gen::SPAN);
+        if let Err(err) = gen::check(rows, span) {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+        print!("{}", gen::generate(&mut rng, rows, span));
+        return;
+    }
+
     let input = std::fs::read_to_string("../input.txt").expect("Failed to read ../input.txt");
 
-    let tables = parse_input(&input);
-    let seed_to_location = SeedToLocation::from_tables(&tables);
+    let mut report = Report::from_env();
+    let seed_to_location = report.parse(|| {
+        let tables = parse_input(&input).unwrap_or_else(|err| panic!("{}", err));
+        SeedToLocation::from_tables(&tables)
+    });
 
-    {
-        let seeds = seed_to_location.seeds();
-        let seed_locations = seeds
+    report.part(1, "Lowest location", || {
+        seed_to_location
+            .seeds()
             .iter()
             .map(|&seed| seed_to_location.seed_to_location(seed))
-            .collect::<Vec<_>>();
+            .min()
+            .unwrap()
+    });
 
-        println!("Lowest location: {}", seed_locations.iter().min().unwrap());
-    }
-
-    {
-        let start = std::time::Instant::now();
+    report.part(2, "Lowest location by seed ranges", || {
         let seed_ranges = seed_to_location.seed_ranges();
-        let seed_locations = seed_to_location.seed_ranges_to_locations(&seed_ranges);
-        println!("Elapsed: {:?}", start.elapsed());
-
-        println!(
-            "Lowest location by seed ranges: {}",
-            seed_locations.iter().min().unwrap()
-        );
-    }
+        seed_to_location
+            .seed_ranges_to_locations(&seed_ranges)
+            .min()
+            .unwrap()
+    });
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -104,6 +125,32 @@ impl From<Table> for MapTable {
     }
 }
 
+impl MapTable {
+    /// Map every value in `sources` at once, range by range instead of value by value.
+    fn map_ranges(&self, sources: &IntervalSet<i64>) -> IntervalSet<i64> {
+        let mut mapped = IntervalSet::new();
+        let mut unmapped = sources.clone();
+
+        // Rows are tried in order, so a value only maps by the first row covering it
+        for i in self.data.chunks_exact(3) {
+            let (src_start, src_end, dst_start) = (i[0], i[1], i[2]);
+            let row = IntervalSet::from(src_start..src_end);
+            let offset = dst_start - src_start;
+
+            let moved = unmapped
+                .intersection(&row)
+                .iter()
+                .map(|range| range.start + offset..range.end + offset)
+                .collect::<IntervalSet<_>>();
+
+            mapped = mapped.union(&moved);
+            unmapped = unmapped.difference(&row);
+        }
+
+        mapped.union(&unmapped)
+    }
+}
+
 impl MapSourceDestination for MapTable {
     fn map_source_destination(&self, source: i64) -> i64 {
         {
@@ -143,64 +190,41 @@ where
     }
 }
 
-fn parse_table(reader: &mut impl Iterator<Item = String>) -> Table {
-    let line = reader.next().unwrap();
-
-    let Some((name, first_row)) = line.split_once(':') else {
-        panic!("Expected table name but got: {:#?}", line);
-    };
-
-    let mut table = Table::new(name);
-    let line = first_row.to_string();
-
-    if !line.is_empty() {
-        table.add(parse_row(&line));
-    }
+/// Tables start with a `name:` header, optionally followed by a first row on the same
+/// line, and end at a blank line.
+fn parse_input(text: &str) -> Result<Vec<Table>, lex::Error> {
+    let header = Pattern::new("{name}: {cells:[i64]}");
+    let row = Pattern::new("{cells:[i64]}");
+
+    let mut tables: Vec<Table> = Vec::new();
+    let mut in_table = false;
+
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            in_table = false;
+        } else if in_table {
+            let cells = row.parse_line(index + 1, line)?.list("cells");
+            tables.last_mut().unwrap().add(Row { cells });
+        } else {
+            let captures = header.parse_line(index + 1, line)?;
+            let mut table = Table::new(captures.str("name"));
 
-    for line in reader {
-        match line.as_str() {
-            "" => {
-                break;
+            let cells = captures.list("cells");
+            if !cells.is_empty() {
+                table.add(Row { cells });
             }
-            line => {
-                table.add(parse_row(line));
-            }
-        }
-    }
-
-    table
-}
-
-fn parse_row(text: &str) -> Row {
-    Row {
-        cells: text
-            .split_ascii_whitespace()
-            .map(|s| {
-                s.trim()
-                    .parse()
-                    .expect(format!("Expected integer but got: {:#?}", s).as_str())
-            })
-            .collect(),
-    }
-}
-
-fn parse_input(text: &str) -> Vec<Table> {
-    let mut tables = Vec::new();
 
-    let mut lines = text.lines().map(|s| s.to_string()).peekable();
-    while let Some(line) = lines.peek() {
-        if line.is_empty() {
-            lines.next();
-        } else {
-            let table = parse_table(&mut lines);
             tables.push(table);
+            in_table = true;
         }
     }
 
-    tables
+    Ok(tables)
 }
+
 trait TableMap {
     fn by_source(&self, source: i64) -> Option<&Row>;
+    #[allow(dead_code)] // Neither part maps backwards
     fn by_destination(&self, destination: i64) -> Option<&Row>;
 }
 
@@ -319,7 +343,7 @@ impl SeedToLocation {
         self.seeds.row(0).cells().copied().collect()
     }
 
-    fn seed_ranges(&self) -> Vec<std::ops::Range<i64>> {
+    fn seed_ranges(&self) -> IntervalSet<i64> {
         self.seeds()
             .chunks_exact(2)
             .map(|chunk| chunk[0]..chunk[0] + chunk[1])
@@ -366,21 +390,36 @@ impl SeedToLocation {
         self.humidity_to_location(humidity)
     }
 
-    fn seed_ranges_to_locations(&self, seed_ranges: &[std::ops::Range<i64>]) -> Vec<i64> {
-        seed_ranges
-            .iter()
-            .flat_map(|seed_range| {
-                println!("seed_range: {:#?}", seed_range);
-                seed_range.clone()
-            })
-            .map(|seed| self.seed_to_location(seed))
-            .collect()
+    fn seed_ranges_to_locations(&self, seed_ranges: &IntervalSet<i64>) -> IntervalSet<i64> {
+        [
+            ("seed-to-soil", &self.seed_to_soil),
+            ("soil-to-fertilizer", &self.soil_to_fertilizer),
+            ("fertilizer-to-water", &self.fertilizer_to_water),
+            ("water-to-light", &self.water_to_light),
+            ("light-to-temperature", &self.light_to_temperature),
+            ("temperature-to-humidity", &self.temperature_to_humidity),
+            ("humidity-to-location", &self.humidity_to_location),
+        ]
+        .iter()
+        .fold(seed_ranges.clone(), |ranges, (name, table)| {
+            for range in &ranges {
+                aoc::trace!("{}: {:?}", name, range);
+            }
+            let mapped = table.map_ranges(&ranges);
+            aoc::debug!(
+                "{}: {} ranges map to {}",
+                name,
+                ranges.ranges().len(),
+                mapped.ranges().len()
+            );
+            mapped
+        })
     }
 }
 
 #[test]
 fn test_input_1() {
-    let tables = parse_input(INPUT_1);
+    let tables = parse_input(INPUT_1).unwrap();
 
     assert_eq!(tables.len(), 8);
     assert_eq!(tables[0].name, "seeds");
@@ -389,9 +428,21 @@ fn test_input_1() {
     assert_eq!(tables[7].row(0).cell(1), 56);
 }
 
+#[test]
+fn test_parse_errors() {
+    let err = parse_input("seeds: 79 14\n\nseed-to-soil map:\n50 x 2").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 4, column 4: expected a number but found \"x\""
+    );
+
+    let err = parse_input("seeds: 79 14\n\n50 98 2").unwrap_err();
+    assert_eq!((err.line, err.expected.as_str()), (3, "\":\""));
+}
+
 #[test]
 fn test_stage_1_seed_to_soil() {
-    let tables = parse_input(INPUT_1);
+    let tables = parse_input(INPUT_1).unwrap();
     let seed_to_location = SeedToLocation::from_tables(&tables);
 
     let seed_soil = seed_to_location
@@ -415,27 +466,71 @@ fn test_stage_1_seed_to_soil() {
 
 #[test]
 fn test_stage_2_seed_ranges() {
-    let tables = parse_input(INPUT_1);
+    let tables = parse_input(INPUT_1).unwrap();
     let seed_to_location = SeedToLocation::from_tables(&tables);
     let seed_range = seed_to_location.seed_ranges();
 
-    assert_eq!(seed_range[0], 79..93);
-    assert_eq!(seed_range[1], 55..68);
+    assert_eq!(seed_range.ranges(), [55..68, 79..93]);
 }
 
 #[test]
 fn test_stage_2_seed_ranges_to_locations() {
-    let tables = parse_input(INPUT_1);
+    let tables = parse_input(INPUT_1).unwrap();
     let seed_to_location = SeedToLocation::from_tables(&tables);
 
     let seed_ranges = seed_to_location.seed_ranges();
     let seed_locations = seed_to_location.seed_ranges_to_locations(&seed_ranges);
 
-    let lowest_location = seed_locations.into_iter().min().unwrap();
+    let lowest_location = seed_locations.min().unwrap();
 
     assert_eq!(lowest_location, 46);
 }
 
+#[test]
+fn test_stage_2_ranges_match_single_seeds() {
+    let tables = parse_input(INPUT_1).unwrap();
+    let seed_to_location = SeedToLocation::from_tables(&tables);
+
+    let seed_ranges = seed_to_location.seed_ranges();
+    let by_seed = seed_ranges
+        .iter()
+        .flat_map(|range| range.clone())
+        .map(|seed| seed_to_location.seed_to_location(seed))
+        .map(|location| location..location + 1)
+        .collect::<IntervalSet<_>>();
+
+    assert_eq!(
+        seed_to_location.seed_ranges_to_locations(&seed_ranges),
+        by_seed
+    );
+}
+
+#[test]
+fn test_generated_almanacs() {
+    for seed in 0..20 {
+        let input = gen::generate(&mut Rng::new(seed), 6, 2000);
+        let tables = parse_input(&input).unwrap();
+        assert_eq!(tables.len(), 8);
+
+        let seed_to_location = SeedToLocation::from_tables(&tables);
+        let seed_ranges = seed_to_location.seed_ranges();
+        let lowest = seed_ranges
+            .iter()
+            .flat_map(|range| range.clone())
+            .map(|seed| seed_to_location.seed_to_location(seed))
+            .min();
+
+        assert_eq!(
+            seed_to_location
+                .seed_ranges_to_locations(&seed_ranges)
+                .min(),
+            lowest,
+            "Seed {}",
+            seed
+        );
+    }
+}
+
 #[cfg(test)]
 const INPUT_1: &str = r#"
 seeds: 79 14 55 13
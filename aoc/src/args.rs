@@ -0,0 +1,128 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// Command line arguments, picked off option by option.
+///
+/// Options may be given as `--name value` or `--name=value`. Whatever is not picked off
+/// stays behind and can be inspected with `rest`.
+#[derive(Debug, Clone)]
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn new<S: Into<String>>(args: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Arguments of the current process, without the program name.
+    pub fn from_env() -> Self {
+        Self::new(std::env::args().skip(1))
+    }
+
+    /// Take the leading positional argument, if there is one.
+    pub fn subcommand(&mut self) -> Option<String> {
+        match self.args.first() {
+            Some(arg) if !arg.starts_with('-') => Some(self.args.remove(0)),
+            _ => None,
+        }
+    }
+
+    /// Take every occurrence of `name`, returning whether it was present.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let before = self.args.len();
+        self.args.retain(|arg| arg != name);
+        self.args.len() != before
+    }
+
+    /// Take every `-v`, `-vv`, ... returning how many `v`s there were in total.
+    pub fn verbosity(&mut self) -> usize {
+        let mut count = 0;
+        self.args.retain(|arg| match arg.strip_prefix('-') {
+            Some(vs) if !vs.is_empty() && vs.chars().all(|c| c == 'v') => {
+                count += vs.len();
+                false
+            }
+            _ => true,
+        });
+        count
+    }
+
+    /// Take the value of option `name`.
+    pub fn value(&mut self, name: &str) -> Option<String> {
+        let prefix = format!("{}=", name);
+
+        let index = self
+            .args
+            .iter()
+            .position(|arg| arg == name || arg.starts_with(&prefix))?;
+        let arg = self.args.remove(index);
+
+        match arg.strip_prefix(&prefix) {
+            Some(value) => Some(value.to_string()),
+            None if index < self.args.len() => Some(self.args.remove(index)),
+            None => panic!("Missing value for {}", name),
+        }
+    }
+
+    /// Take the value of option `name` and parse it.
+    pub fn parse<T>(&mut self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Debug,
+    {
+        self.value(name).map(|value| match value.parse() {
+            Ok(parsed) => parsed,
+            Err(err) => panic!("Invalid value for {}: {:#?} ({:?})", name, value, err),
+        })
+    }
+
+    /// Arguments that were not taken.
+    pub fn rest(&self) -> &[String] {
+        &self.args
+    }
+}
+
+#[test]
+fn test_subcommand() {
+    let mut args = Args::new(["render", "--day", "3"]);
+    assert_eq!(args.subcommand().as_deref(), Some("render"));
+    assert_eq!(args.subcommand(), None);
+
+    let mut args = Args::new(["--day", "3"]);
+    assert_eq!(args.subcommand(), None);
+}
+
+#[test]
+fn test_options() {
+    let mut args = Args::new(["--day", "3", "--html=out.html", "--verbose", "extra"]);
+
+    assert!(args.flag("--verbose"));
+    assert!(!args.flag("--verbose"));
+    assert_eq!(args.parse::<u8>("--day"), Some(3));
+    assert_eq!(args.value("--html").as_deref(), Some("out.html"));
+    assert_eq!(args.value("--year"), None);
+    assert_eq!(args.rest(), ["extra"]);
+}
+
+#[test]
+fn test_verbosity() {
+    let mut args = Args::new(["-v", "--day", "3", "-vv", "--verbose"]);
+    assert_eq!(args.verbosity(), 3);
+    assert_eq!(args.verbosity(), 0);
+    assert_eq!(args.rest(), ["--day", "3", "--verbose"]);
+}
+
+#[test]
+#[should_panic(expected = "Missing value for --day")]
+fn test_missing_value() {
+    Args::new(["--day"]).value("--day");
+}
+
+#[test]
+#[should_panic(expected = "Invalid value for --day")]
+fn test_invalid_value() {
+    Args::new(["--day", "three"]).parse::<u8>("--day");
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The repository checkout this crate was built from; years live directly below it.
+pub fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("Crate has no parent directory")
+        .to_path_buf()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Day {
+    pub year: u16,
+    pub day: u8,
+}
+
+impl Day {
+    pub const fn new(year: u16, day: u8) -> Self {
+        Self { year, day }
+    }
+
+    /// `<root>/<year>/<day>`, holding the Makefile and the puzzle input.
+    pub fn dir(&self, root: &Path) -> PathBuf {
+        root.join(self.year.to_string()).join(self.day.to_string())
+    }
+
+    /// The day's cargo package.
+    pub fn crate_dir(&self, root: &Path) -> PathBuf {
+        self.dir(root).join("rust")
+    }
+
+    pub fn exists(&self, root: &Path) -> bool {
+        self.crate_dir(root).join("Cargo.toml").is_file()
+    }
+
+    /// `cargo build --release` of the day's binary, see `release_binary`.
+    pub fn cargo_build(&self, root: &Path) -> Command {
+        let mut command = Command::new("cargo");
+        command
+            .args(["build", "--release", "--quiet"])
+            .current_dir(self.crate_dir(root));
+        command
+    }
+
+    /// The day's binary as built by `cargo_build`, set to run inside its crate.
+    pub fn release_binary(&self, root: &Path) -> Command {
+        let mut command = Command::new(self.crate_dir(root).join("target/release/rust"));
+        command.current_dir(self.crate_dir(root));
+        command
+    }
+
+    /// `cargo run` of the day's binary. It runs inside the crate so `../input.txt` resolves.
+    pub fn cargo_run(&self, root: &Path, args: &[String]) -> Command {
+        let mut command = Command::new("cargo");
+        command
+            .args(["run", "--release", "--quiet", "--"])
+            .args(args)
+            .current_dir(self.crate_dir(root));
+        command
+    }
+}
+
+impl fmt::Display for Day {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} day {}", self.year, self.day)
+    }
+}
+
+#[test]
+fn test_day_paths() {
+    let day = Day::new(2023, 3);
+    let root = Path::new("/repo");
+
+    assert_eq!(day.dir(root), Path::new("/repo/2023/3"));
+    assert_eq!(day.crate_dir(root), Path::new("/repo/2023/3/rust"));
+    assert_eq!(day.to_string(), "2023 day 3");
+}
+
+#[test]
+fn test_repo_root() {
+    let root = repo_root();
+
+    assert!(root.join("aoc").join("Cargo.toml").is_file());
+    assert!(Day::new(2023, 1).exists(&root));
+    assert!(!Day::new(2023, 26).exists(&root));
+}
@@ -0,0 +1,21 @@
+//! Every day the runner knows about. `aoc new` registers new days above the marker.
+
+use crate::day::Day;
+
+pub const DAYS: &[Day] = &[
+    Day::new(2023, 1),
+    Day::new(2023, 2),
+    Day::new(2023, 3),
+    Day::new(2023, 4),
+    Day::new(2023, 5),
+    // aoc new: register days above this line
+];
+
+#[test]
+fn test_registered_days_exist() {
+    let root = crate::day::repo_root();
+
+    for day in DAYS {
+        assert!(day.exists(&root), "{} is registered but missing", day);
+    }
+}
@@ -0,0 +1,495 @@
+use std::fmt;
+use std::ops::{Index, IndexMut, RangeInclusive};
+
+/// Offsets of the four orthogonal neighbours, clockwise from the top.
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of all eight neighbours, row by row.
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// A line's length differs from the first line; `line` counts from 1.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {} has {} cells but expected {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Lines of a text grid: `\r\n` endings are accepted and trailing blank lines dropped.
+pub fn grid_lines(text: &str) -> Vec<&str> {
+    let mut lines = text.lines().collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Rectangular grid of cells stored row by row in a single `Vec`.
+///
+/// Checked access takes `isize` coordinates so callers can look one cell past an edge
+/// (`x - 1`, `y + 1`) and simply get `None` back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "Cell count mismatch");
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        let height = rows.len();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            assert_eq!(row.len(), width, "Row {} length mismatch", y);
+            cells.extend(row);
+        }
+
+        Self::new(width, height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y)
+    }
+
+    fn offset(&self, x: isize, y: isize) -> Option<usize> {
+        self.contains(x, y)
+            .then(|| y as usize * self.width + x as usize)
+    }
+
+    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
+        self.offset(x, y).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, x: isize, y: isize) -> Option<&mut T> {
+        self.offset(x, y).map(|i| &mut self.cells[i])
+    }
+
+    /// Access that wraps around the edges, as if the grid tiles the plane.
+    pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+        assert!(!self.is_empty(), "Wrapping access into an empty grid");
+        let x = x.rem_euclid(self.width as isize);
+        let y = y.rem_euclid(self.height as isize);
+        &self[(x, y)]
+    }
+
+    /// All cells with their coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((isize, isize), &T)> {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (((i % width) as isize, (i / width) as isize), cell))
+    }
+
+    /// Orthogonal neighbours of `(x, y)` that lie inside the grid.
+    pub fn neighbours4(&self, x: isize, y: isize) -> impl Iterator<Item = ((isize, isize), &T)> {
+        self.around(x, y, &NEIGHBOURS_4)
+    }
+
+    /// Orthogonal and diagonal neighbours of `(x, y)` that lie inside the grid.
+    pub fn neighbours8(&self, x: isize, y: isize) -> impl Iterator<Item = ((isize, isize), &T)> {
+        self.around(x, y, &NEIGHBOURS_8)
+    }
+
+    fn around<'a>(
+        &'a self,
+        x: isize,
+        y: isize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = ((isize, isize), &'a T)> {
+        offsets.iter().filter_map(move |(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            self.get(nx, ny).map(|cell| ((nx, ny), cell))
+        })
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Cells inside the given window, clipped to the grid, row by row.
+    pub fn window(
+        &self,
+        rx: RangeInclusive<isize>,
+        ry: RangeInclusive<isize>,
+    ) -> impl Iterator<Item = ((isize, isize), &T)> {
+        let rx = *rx.start().max(&0)..=*rx.end().min(&(self.width as isize - 1));
+        let ry = *ry.start().max(&0)..=*ry.end().min(&(self.height as isize - 1));
+
+        ry.flat_map(move |y| {
+            rx.clone()
+                .map(move |x| ((x, y), &self.cells[y as usize * self.width + x as usize]))
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    /// Mirror along the main diagonal; row `n` becomes column `n`.
+    pub fn transpose(&self) -> Self {
+        let cells = self.columns().flatten().cloned().collect();
+        Self::new(self.height, self.width, cells)
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        let cells = self
+            .columns()
+            .flat_map(|column| column.collect::<Vec<_>>().into_iter().rev())
+            .cloned()
+            .collect();
+        Self::new(self.height, self.width, cells)
+    }
+
+    /// Rotate a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|x| self.column(x))
+            .cloned()
+            .collect();
+        Self::new(self.height, self.width, cells)
+    }
+}
+
+impl<T: From<char>> Grid<T> {
+    /// Parse one row per line, converting every char into a cell.
+    ///
+    /// Every line must be as long as the first one; see `grid_lines` for what counts as a line.
+    pub fn try_parse(text: &str) -> Result<Self, GridError> {
+        let lines = grid_lines(text);
+        let width = lines.first().map_or(0, |line| line.chars().count());
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(T::from));
+
+            let found = cells.len() - before;
+            if found != width {
+                return Err(GridError::Ragged {
+                    line: i + 1,
+                    expected: width,
+                    found,
+                });
+            }
+        }
+
+        Ok(Self::new(width, lines.len(), cells))
+    }
+
+    /// Like `try_parse`, panicking on ragged input.
+    pub fn parse(text: &str) -> Self {
+        Self::try_parse(text).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl<T: From<char> + Clone> Grid<T> {
+    /// Parse rows of any length; the grid is as wide as the longest line and shorter
+    /// lines are padded on the right with `fill`.
+    pub fn parse_padded(text: &str, fill: T) -> Self {
+        let lines = grid_lines(text);
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for line in &lines {
+            let before = cells.len();
+            cells.extend(line.chars().map(T::from));
+            cells.resize(before + width, fill.clone());
+        }
+
+        Self::new(width, lines.len(), cells)
+    }
+}
+
+impl<T> Index<(isize, isize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (isize, isize)) -> &T {
+        match self.get(x, y) {
+            Some(cell) => cell,
+            None => panic!("Position ({}, {}) out of bounds", x, y),
+        }
+    }
+}
+
+impl<T> IndexMut<(isize, isize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (isize, isize)) -> &mut T {
+        match self.get_mut(x, y) {
+            Some(cell) => cell,
+            None => panic!("Position ({}, {}) out of bounds", x, y),
+        }
+    }
+}
+
+#[cfg(test)]
+fn to_strings(grid: &Grid<char>) -> Vec<String> {
+    grid.rows().map(|row| row.iter().collect()).collect()
+}
+
+#[test]
+fn test_parse() {
+    let grid = Grid::<char>::parse("abc\ndef\n");
+
+    assert_eq!(grid.width(), 3);
+    assert_eq!(grid.height(), 2);
+    assert_eq!(grid.get(0, 0), Some(&'a'));
+    assert_eq!(grid.get(2, 1), Some(&'f'));
+    assert_eq!(grid[(1, 1)], 'e');
+}
+
+#[test]
+#[should_panic(expected = "Line 2 has 2 cells but expected 3")]
+fn test_parse_ragged() {
+    Grid::<char>::parse("abc\nde");
+}
+
+#[test]
+fn test_try_parse() {
+    assert_eq!(
+        Grid::<char>::try_parse("abc\nabc\nabcd\nab"),
+        Err(GridError::Ragged {
+            line: 3,
+            expected: 3,
+            found: 4
+        })
+    );
+
+    // A blank line in the middle is not trailing
+    assert_eq!(
+        Grid::<char>::try_parse("abc\n\nabc"),
+        Err(GridError::Ragged {
+            line: 2,
+            expected: 3,
+            found: 0
+        })
+    );
+}
+
+#[test]
+fn test_parse_line_endings() {
+    let expected = Grid::<char>::parse("ab\ncd");
+
+    assert_eq!(Grid::<char>::parse("ab\r\ncd\r\n"), expected);
+    assert_eq!(Grid::<char>::parse("ab\ncd\n\n\n"), expected);
+    assert_eq!(Grid::<char>::parse("ab\r\ncd\r\n\r\n"), expected);
+    assert!(Grid::<char>::parse("\n\n").is_empty());
+}
+
+#[test]
+fn test_parse_padded() {
+    let grid = Grid::parse_padded("abc\nd\n\nef\r\n\n", ' ');
+
+    assert_eq!(grid.width(), 3);
+    assert_eq!(grid.height(), 4);
+    assert_eq!(to_strings(&grid), ["abc", "d  ", "   ", "ef "]);
+
+    assert!(Grid::parse_padded("", ' ').is_empty());
+}
+
+#[test]
+fn test_checked_access() {
+    let grid = Grid::<char>::parse("ab\ncd");
+
+    assert_eq!(grid.get(-1, 0), None);
+    assert_eq!(grid.get(0, -1), None);
+    assert_eq!(grid.get(2, 0), None);
+    assert_eq!(grid.get(0, 2), None);
+    assert!(grid.contains(1, 1));
+    assert!(!grid.contains(1, 2));
+}
+
+#[test]
+fn test_wrapping_access() {
+    let grid = Grid::<char>::parse("ab\ncd");
+
+    assert_eq!(grid.get_wrapping(2, 0), &'a');
+    assert_eq!(grid.get_wrapping(-1, 0), &'b');
+    assert_eq!(grid.get_wrapping(-1, -1), &'d');
+    assert_eq!(grid.get_wrapping(5, 7), &'d');
+}
+
+#[test]
+fn test_neighbours() {
+    let grid = Grid::<char>::parse("abc\ndef\nghi");
+
+    let centre = grid.neighbours4(1, 1).map(|(_, c)| *c).collect::<String>();
+    assert_eq!(centre, "bfhd");
+
+    let corner = grid.neighbours4(0, 0).map(|(_, c)| *c).collect::<String>();
+    assert_eq!(corner, "bd");
+
+    let centre = grid.neighbours8(1, 1).map(|(_, c)| *c).collect::<String>();
+    assert_eq!(centre, "abcdfghi");
+
+    let corner = grid
+        .neighbours8(2, 2)
+        .map(|(pos, _)| pos)
+        .collect::<Vec<_>>();
+    assert_eq!(corner, [(1, 1), (2, 1), (1, 2)]);
+}
+
+#[test]
+fn test_rows_columns() {
+    let grid = Grid::<char>::parse("abc\ndef");
+
+    assert_eq!(to_strings(&grid), ["abc", "def"]);
+    assert_eq!(grid.column(1).collect::<String>(), "be");
+
+    let columns = grid
+        .columns()
+        .map(|column| column.collect::<String>())
+        .collect::<Vec<_>>();
+    assert_eq!(columns, ["ad", "be", "cf"]);
+}
+
+#[test]
+fn test_window() {
+    let grid = Grid::<char>::parse("abc\ndef\nghi");
+
+    let window = grid
+        .window(-1..=1, -1..=1)
+        .map(|(_, c)| *c)
+        .collect::<String>();
+    assert_eq!(window, "abde");
+
+    let window = grid
+        .window(1..=5, 1..=1)
+        .map(|(_, c)| *c)
+        .collect::<String>();
+    assert_eq!(window, "ef");
+
+    assert_eq!(grid.window(3..=4, 0..=2).count(), 0);
+}
+
+#[test]
+fn test_iter() {
+    let grid = Grid::<char>::parse("ab\ncd");
+
+    let cells = grid.iter().collect::<Vec<_>>();
+    assert_eq!(
+        cells,
+        [
+            ((0, 0), &'a'),
+            ((1, 0), &'b'),
+            ((0, 1), &'c'),
+            ((1, 1), &'d')
+        ]
+    );
+}
+
+#[test]
+fn test_transpose_rotate() {
+    let grid = Grid::<char>::parse("abc\ndef");
+
+    assert_eq!(to_strings(&grid.transpose()), ["ad", "be", "cf"]);
+    assert_eq!(to_strings(&grid.rotate_cw()), ["da", "eb", "fc"]);
+    assert_eq!(to_strings(&grid.rotate_ccw()), ["cf", "be", "ad"]);
+
+    let full_turn = grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+    assert_eq!(full_turn, grid);
+    assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+}
+
+#[test]
+fn test_empty() {
+    let grid = Grid::<char>::parse("");
+
+    assert!(grid.is_empty());
+    assert_eq!(grid.width(), 0);
+    assert_eq!(grid.height(), 0);
+    assert_eq!(grid.get(0, 0), None);
+    assert_eq!(grid.rows().count(), 0);
+    assert_eq!(grid.iter().count(), 0);
+}
+
+#[test]
+fn test_mutation() {
+    let mut grid = Grid::filled(2, 2, '.');
+
+    grid[(1, 0)] = '#';
+    *grid.get_mut(0, 1).unwrap() = '@';
+    assert_eq!(grid.get_mut(2, 2), None);
+
+    assert_eq!(to_strings(&grid), [".#", "@."]);
+    assert_eq!(
+        to_strings(&grid.map(|c| if *c == '.' { ' ' } else { *c })),
+        [" #", "@ "]
+    );
+}
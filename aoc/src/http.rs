@@ -0,0 +1,225 @@
+//! Minimal client for the puzzle site, run through `curl` so no crates are needed.
+//!
+//! The site is `AOC_BASE_URL`, by default `https://adventofcode.com`, so a local stub can
+//! stand in for it. `AOC_SESSION` holds the session cookie from the browser.
+
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The site asks automated clients to say where they come from.
+const USER_AGENT: &str = "github.com/Zaibot/advent-of-code aoc runner";
+
+#[derive(Debug)]
+pub enum HttpError {
+    /// A page that needs logging in was requested without `AOC_SESSION`.
+    MissingSession,
+    /// `curl` could not be started.
+    Spawn(std::io::Error),
+    /// The request failed: no connection, or a status of 400 and up.
+    Failed { url: String, reason: String },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HttpError::MissingSession => write!(
+                f,
+                "Set environment variable AOC_SESSION to match session cookie in the browser"
+            ),
+            HttpError::Spawn(err) => write!(f, "Failed to run curl: {}", err),
+            HttpError::Failed { url, reason } => write!(f, "{}: {}", url, reason),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Client {
+    base_url: String,
+    session: Option<String>,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+        }
+    }
+
+    /// Configured by `AOC_BASE_URL` and `AOC_SESSION`.
+    pub fn from_env() -> Self {
+        let base_url = std::env::var("AOC_BASE_URL").unwrap_or(DEFAULT_BASE_URL.to_string());
+        let session = std::env::var("AOC_SESSION")
+            .ok()
+            .filter(|session| !session.is_empty());
+        Self::new(&base_url, session)
+    }
+
+    pub fn has_session(&self) -> bool {
+        self.session.is_some()
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// The session goes in through a config on stdin, so it doesn't show in `ps`.
+    fn curl(&self, path: &str) -> Command {
+        let mut command = Command::new("curl");
+        command
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            .args(["--user-agent", USER_AGENT, "--config", "-"])
+            .arg(self.url(path))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    /// Body of the page at `path`, which is relative to the base URL.
+    pub fn get(&self, path: &str) -> Result<String, HttpError> {
+        self.send(path, self.curl(path))
+    }
+
+    /// Like `get`, but fails early without a session.
+    pub fn get_private(&self, path: &str) -> Result<String, HttpError> {
+        if !self.has_session() {
+            return Err(HttpError::MissingSession);
+        }
+        self.get(path)
+    }
+
+    /// Post `fields` as a form, returning the body of the response.
+    pub fn post_form(&self, path: &str, fields: &[(&str, &str)]) -> Result<String, HttpError> {
+        if !self.has_session() {
+            return Err(HttpError::MissingSession);
+        }
+
+        let mut command = self.curl(path);
+        for (name, value) in fields {
+            command.args(["--data-urlencode", &format!("{}={}", name, value)]);
+        }
+        self.send(path, command)
+    }
+
+    fn send(&self, path: &str, mut command: Command) -> Result<String, HttpError> {
+        let mut child = command.spawn().map_err(HttpError::Spawn)?;
+        let mut config = child.stdin.take().expect("curl has no stdin");
+        if let Some(session) = &self.session {
+            writeln!(config, "cookie = \"session={}\"", session).map_err(HttpError::Spawn)?;
+        }
+        drop(config);
+
+        let output = child.wait_with_output().map_err(HttpError::Spawn)?;
+        if !output.status.success() {
+            return Err(HttpError::Failed {
+                url: self.url(path),
+                reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Local stand-in for the site: answers each request with the next of `responses`, as
+/// status and body, and hands back the requests it got.
+#[cfg(test)]
+pub(crate) fn stub_server(
+    responses: Vec<(u16, String)>,
+) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let mut content = vec![0; length];
+            reader.read_exact(&mut content).unwrap();
+            request.push_str(&String::from_utf8_lossy(&content));
+            requests.push(request);
+
+            let response = format!(
+                "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+
+    (base_url, server)
+}
+
+#[test]
+fn test_get() {
+    let (base_url, server) = stub_server(vec![(200, "1abc2\n".to_string())]);
+    let client = Client::new(&format!("{}/", base_url), Some("cookie".to_string()));
+
+    assert_eq!(client.get("/2023/day/1/input").unwrap(), "1abc2\n");
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /2023/day/1/input HTTP/1.1\r\n"));
+    assert!(requests[0].contains("Cookie: session=cookie\r\n"));
+    assert!(requests[0].contains(USER_AGENT));
+}
+
+#[test]
+fn test_post_form() {
+    let (base_url, server) = stub_server(vec![(200, "<main>ok</main>".to_string())]);
+    let client = Client::new(&base_url, Some("cookie".to_string()));
+
+    let body = client
+        .post_form("/2023/day/1/answer", &[("level", "1"), ("answer", "a&b=c")])
+        .unwrap();
+    assert_eq!(body, "<main>ok</main>");
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("POST /2023/day/1/answer HTTP/1.1\r\n"));
+    assert!(
+        requests[0].ends_with("\r\n\r\nlevel=1&answer=a%26b%3Dc"),
+        "{:?}",
+        requests[0]
+    );
+}
+
+#[test]
+fn test_errors() {
+    let (base_url, server) = stub_server(vec![(404, "Not found".to_string())]);
+    let client = Client::new(&base_url, None);
+
+    let err = client.get("/2023/day/26").unwrap_err();
+    assert!(matches!(&err, HttpError::Failed { url, .. } if url.ends_with("/2023/day/26")));
+    assert!(err.to_string().contains("404"), "{}", err);
+    server.join().unwrap();
+
+    assert!(matches!(
+        client.get_private("/2023/day/1/input"),
+        Err(HttpError::MissingSession)
+    ));
+}
@@ -0,0 +1,303 @@
+use std::ops::Range;
+
+/// Set of values stored as half-open ranges.
+///
+/// The ranges are kept sorted, non-empty and apart from each other: touching or
+/// overlapping ranges are merged as soon as they are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T: Ord + Copy> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set covering every value of `ranges`, which may be unsorted, overlap or be empty.
+    pub fn coalesce(ranges: impl IntoIterator<Item = Range<T>>) -> Self {
+        let mut ranges = ranges
+            .into_iter()
+            .filter(|range| range.start < range.end)
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let ranges = std::mem::take(&mut self.ranges);
+        *self = Self::coalesce(ranges.into_iter().chain([range]));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The covered ranges, in order.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges.get(i).is_some_and(|range| range.start <= value)
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    /// The end of the last range; not itself in the set.
+    pub fn max_end(&self) -> Option<T> {
+        self.ranges.last().map(|range| range.end)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::coalesce(self.iter().chain(other.iter()).cloned())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let start = x.start.max(y.start);
+            let end = x.end.min(y.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            // Drop whichever range ends first; the other may still overlap the next one
+            if x.end <= y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut others = other.iter().peekable();
+
+        for range in self.iter() {
+            let mut start = range.start;
+
+            while let Some(cut) = others.peek() {
+                if cut.end <= start {
+                    others.next();
+                    continue;
+                }
+                if cut.start >= range.end {
+                    break;
+                }
+
+                if start < cut.start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                if cut.end > range.end {
+                    break;
+                }
+                others.next();
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// Values below `at`, and values from `at` on.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+
+        for range in self.iter() {
+            if range.end <= at {
+                below.push(range.clone());
+            } else if range.start >= at {
+                above.push(range.clone());
+            } else {
+                below.push(range.start..at);
+                above.push(at..range.end);
+            }
+        }
+
+        (Self { ranges: below }, Self { ranges: above })
+    }
+
+    /// Parts of `within` that are not covered, in order.
+    pub fn gaps(&self, within: Range<T>) -> impl Iterator<Item = Range<T>> + '_ {
+        IntervalSet::from(within)
+            .difference(self)
+            .ranges
+            .into_iter()
+    }
+}
+
+impl<T: Ord + Copy> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::coalesce([range])
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        Self::coalesce(iter)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+use crate::rand::Rng;
+#[cfg(test)]
+use std::collections::BTreeSet;
+
+/// Values are kept below this in the property tests so sets collide often.
+#[cfg(test)]
+const DOMAIN: i64 = 40;
+
+#[cfg(test)]
+fn random_set(rng: &mut Rng) -> (IntervalSet<i64>, BTreeSet<i64>) {
+    let ranges = (0..rng.below(5))
+        .map(|_| {
+            let start = rng.range(0..DOMAIN as u64) as i64;
+            let len = rng.below(10) as i64;
+            start..(start + len).min(DOMAIN)
+        })
+        .collect::<Vec<_>>();
+
+    let model = ranges.iter().flat_map(|range| range.clone()).collect();
+    (IntervalSet::coalesce(ranges), model)
+}
+
+#[cfg(test)]
+fn values(set: &IntervalSet<i64>) -> BTreeSet<i64> {
+    set.iter().flat_map(|range| range.clone()).collect()
+}
+
+/// Sorted, non-empty, and neither overlapping nor touching.
+#[cfg(test)]
+fn assert_canonical(set: &IntervalSet<i64>) {
+    assert!(set.iter().all(|range| range.start < range.end), "{:?}", set);
+    assert!(
+        set.ranges()
+            .windows(2)
+            .all(|pair| pair[0].end < pair[1].start),
+        "{:?}",
+        set
+    );
+}
+
+#[test]
+fn test_coalesce() {
+    let set = IntervalSet::coalesce([10..12, 1..3, 2..5, 5..7, 8..8, Range { start: 20, end: 15 }]);
+
+    assert_eq!(set.ranges(), [1..7, 10..12]);
+    assert_eq!(set.min(), Some(1));
+    assert_eq!(set.max_end(), Some(12));
+    assert!(IntervalSet::<i64>::new().is_empty());
+}
+
+#[test]
+fn test_operations() {
+    let a = IntervalSet::coalesce([0..10, 20..30]);
+    let b = IntervalSet::from(5..25);
+
+    assert_eq!(a.union(&b), IntervalSet::from(0..30));
+    assert_eq!(a.intersection(&b).ranges(), [5..10, 20..25]);
+    assert_eq!(a.difference(&b).ranges(), [0..5, 25..30]);
+    assert_eq!(b.difference(&a), IntervalSet::from(10..20));
+
+    let (below, above) = a.split_at(25);
+    assert_eq!(below.ranges(), [0..10, 20..25]);
+    assert_eq!(above, IntervalSet::from(25..30));
+
+    assert_eq!(a.gaps(-5..35).collect::<Vec<_>>(), [-5..0, 10..20, 30..35]);
+}
+
+#[test]
+fn test_insert_and_contains() {
+    let mut set = IntervalSet::new();
+    set.insert(3..5);
+    set.insert(7..9);
+    set.insert(5..7);
+
+    assert_eq!(set, IntervalSet::from(3..9));
+    assert!(!set.contains(2));
+    assert!(set.contains(3));
+    assert!(set.contains(8));
+    assert!(!set.contains(9));
+}
+
+#[test]
+fn test_properties() {
+    for seed in 0..1000 {
+        let mut rng = Rng::new(seed);
+        let (a, model_a) = random_set(&mut rng);
+        let (b, model_b) = random_set(&mut rng);
+        let at = rng.range(0..DOMAIN as u64) as i64;
+
+        assert_eq!(values(&a), model_a, "Seed {}", seed);
+        assert_canonical(&a);
+
+        let union = a.union(&b);
+        assert_canonical(&union);
+        assert_eq!(values(&union), &model_a | &model_b, "Seed {}", seed);
+
+        let intersection = a.intersection(&b);
+        assert_canonical(&intersection);
+        assert_eq!(values(&intersection), &model_a & &model_b, "Seed {}", seed);
+
+        let difference = a.difference(&b);
+        assert_canonical(&difference);
+        assert_eq!(values(&difference), &model_a - &model_b, "Seed {}", seed);
+
+        let (below, above) = a.split_at(at);
+        assert_canonical(&below);
+        assert_canonical(&above);
+        assert!(values(&below).iter().all(|&v| v < at), "Seed {}", seed);
+        assert!(values(&above).iter().all(|&v| v >= at), "Seed {}", seed);
+        assert_eq!(&values(&below) | &values(&above), model_a, "Seed {}", seed);
+
+        let gaps = a.gaps(0..DOMAIN).flatten().collect::<BTreeSet<_>>();
+        let all = (0..DOMAIN).collect::<BTreeSet<_>>();
+        assert_eq!(gaps, &all - &model_a, "Seed {}", seed);
+
+        for value in 0..DOMAIN {
+            assert_eq!(a.contains(value), model_a.contains(&value), "Seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! Tokens with spans, and a cursor to parse them with, for inputs that read as a stream
+//! of words, numbers and punctuation, like day 2's games.
+//!
+//! Inputs that are one fixed format per line, like days 4 and 5, are declared as
+//! templates in `pattern` instead. Both report mistakes as this module's `Error`, so
+//! every day's parse errors read the same.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Byte range of a token in the lexed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// 1-based line and column of the start of the span in `input`.
+    pub fn position(&self, input: &str) -> (usize, usize) {
+        let before = &input[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        (line, column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A run of letters.
+    Word,
+    /// A run of ASCII digits.
+    Number,
+    /// A single punctuation character.
+    Punct(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+impl Token<'_> {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text == keyword
+    }
+
+    pub fn is_punct(&self, c: char) -> bool {
+        self.kind == TokenKind::Punct(c)
+    }
+}
+
+/// What was expected and what was found instead, at a position in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    /// The offending token, or `None` at the end of the input.
+    pub found: Option<String>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: expected {} but found ",
+            self.line, self.column, self.expected
+        )?;
+        match &self.found {
+            Some(found) => write!(f, "{:?}", found),
+            None => write!(f, "end of input"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Splits input into words, numbers and punctuation.
+///
+/// Separators are skipped, punctuation becomes a token of its own and anything else
+/// that is not a letter or digit is an error. Whitespace is always a separator unless it
+/// is listed as punctuation, so `'\n'` can be made into a token.
+#[derive(Debug, Clone, Default)]
+pub struct Lexer {
+    separators: Vec<char>,
+    punctuation: Vec<char>,
+}
+
+impl Lexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skip `separators` besides whitespace.
+    pub fn separators(mut self, separators: &[char]) -> Self {
+        self.separators.extend_from_slice(separators);
+        self
+    }
+
+    /// Emit each of `punctuation` as a token.
+    pub fn punctuation(mut self, punctuation: &[char]) -> Self {
+        self.punctuation.extend_from_slice(punctuation);
+        self
+    }
+
+    pub fn tokenize<'a>(&self, input: &'a str) -> Result<Vec<Token<'a>>, Error> {
+        let mut tokens = Vec::new();
+        let mut chars = input.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            let kind = if self.punctuation.contains(&c) {
+                TokenKind::Punct(c)
+            } else if c.is_whitespace() || self.separators.contains(&c) {
+                continue;
+            } else if c.is_ascii_digit() {
+                TokenKind::Number
+            } else if c.is_alphabetic() {
+                TokenKind::Word
+            } else {
+                let span = Span {
+                    start,
+                    end: start + c.len_utf8(),
+                };
+                let (line, column) = span.position(input);
+                return Err(Error {
+                    line,
+                    column,
+                    expected: "a word, number or punctuation".to_string(),
+                    found: Some(c.to_string()),
+                });
+            };
+
+            let mut end = start + c.len_utf8();
+            let continues = |c: char| match kind {
+                TokenKind::Number => c.is_ascii_digit(),
+                TokenKind::Word => c.is_alphabetic(),
+                TokenKind::Punct(_) => false,
+            };
+            while let Some(&(i, c)) = chars.peek() {
+                if !continues(c) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            tokens.push(Token {
+                kind,
+                text: &input[start..end],
+                span: Span { start, end },
+            });
+        }
+
+        Ok(tokens)
+    }
+
+    pub fn cursor<'a>(&self, input: &'a str) -> Result<Cursor<'a>, Error> {
+        Ok(Cursor {
+            input,
+            tokens: self.tokenize(input)?,
+            next: 0,
+        })
+    }
+}
+
+/// Walks the tokens of an input, with `expect_*` helpers that report what went wrong
+/// and where.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    tokens: Vec<Token<'a>>,
+    next: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.next)
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.next >= self.tokens.len()
+    }
+
+    /// An error at the next token.
+    pub fn error(&self, expected: &str) -> Error {
+        let (start, found) = match self.peek() {
+            Some(token) => (token.span.start, Some(token.text.to_string())),
+            None => (self.input.len(), None),
+        };
+        let (line, column) = Span { start, end: start }.position(self.input);
+
+        Error {
+            line,
+            column,
+            expected: expected.to_string(),
+            found,
+        }
+    }
+
+    /// Take the next token if it is the punctuation `c`.
+    pub fn eat_punct(&mut self, c: char) -> bool {
+        let found = self.peek().is_some_and(|token| token.is_punct(c));
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    pub fn expect_punct(&mut self, c: char) -> Result<Span, Error> {
+        match self.peek() {
+            Some(token) if token.is_punct(c) => Ok(self.advance().span),
+            _ => Err(self.error(&format!("{:?}", c.to_string()))),
+        }
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<Span, Error> {
+        match self.peek() {
+            Some(token) if token.is_keyword(keyword) => Ok(self.advance().span),
+            _ => Err(self.error(&format!("{:?}", keyword))),
+        }
+    }
+
+    /// Take the next token if it is one of `keywords`.
+    pub fn expect_any_keyword(&mut self, keywords: &[&str]) -> Result<&'a str, Error> {
+        match self.peek() {
+            Some(token) if keywords.iter().any(|k| token.is_keyword(k)) => Ok(self.advance().text),
+            _ => {
+                let expected = keywords
+                    .iter()
+                    .map(|k| format!("{:?}", k))
+                    .collect::<Vec<_>>();
+                Err(self.error(&format!("one of {}", expected.join(", "))))
+            }
+        }
+    }
+
+    pub fn expect_word(&mut self) -> Result<&'a str, Error> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Word => Ok(self.advance().text),
+            _ => Err(self.error("a word")),
+        }
+    }
+
+    /// Take the next token as a number, which must fit in `T`.
+    pub fn expect_number<T: FromStr>(&mut self) -> Result<T, Error> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Number => match token.text.parse() {
+                Ok(number) => {
+                    self.next += 1;
+                    Ok(number)
+                }
+                Err(_) => {
+                    Err(self.error(&format!("a number that fits in {}", short_type_name::<T>())))
+                }
+            },
+            _ => Err(self.error("a number")),
+        }
+    }
+
+    pub fn expect_end(&self) -> Result<(), Error> {
+        if self.is_at_end() {
+            Ok(())
+        } else {
+            Err(self.error("end of input"))
+        }
+    }
+
+    fn advance(&mut self) -> Token<'a> {
+        let token = self.tokens[self.next];
+        self.next += 1;
+        token
+    }
+}
+
+impl<'a> Iterator for Cursor<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (!self.is_at_end()).then(|| self.advance())
+    }
+}
+
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+fn kinds<'a>(tokens: &[Token<'a>]) -> Vec<(TokenKind, &'a str)> {
+    tokens
+        .iter()
+        .map(|token| (token.kind, token.text))
+        .collect()
+}
+
+#[test]
+fn test_tokenize() {
+    let lexer = Lexer::new().separators(&[':', ',']).punctuation(&[';']);
+    let tokens = lexer.tokenize("Game 12: 3 blue, 4 red;1 red").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        [
+            (TokenKind::Word, "Game"),
+            (TokenKind::Number, "12"),
+            (TokenKind::Number, "3"),
+            (TokenKind::Word, "blue"),
+            (TokenKind::Number, "4"),
+            (TokenKind::Word, "red"),
+            (TokenKind::Punct(';'), ";"),
+            (TokenKind::Number, "1"),
+            (TokenKind::Word, "red"),
+        ]
+    );
+    assert_eq!(tokens[1].span, Span { start: 5, end: 7 });
+}
+
+#[test]
+fn test_tokenize_newlines_as_punctuation() {
+    let lexer = Lexer::new().punctuation(&['\n']);
+    let tokens = lexer.tokenize("a 1\r\n\nb").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        [
+            (TokenKind::Word, "a"),
+            (TokenKind::Number, "1"),
+            (TokenKind::Punct('\n'), "\n"),
+            (TokenKind::Punct('\n'), "\n"),
+            (TokenKind::Word, "b"),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_unexpected_character() {
+    let err = Lexer::new().tokenize("ab\ncd $").unwrap_err();
+
+    assert_eq!((err.line, err.column), (2, 4));
+    assert_eq!(
+        err.to_string(),
+        "Line 2, column 4: expected a word, number or punctuation but found \"$\""
+    );
+}
+
+#[test]
+fn test_span_position() {
+    let input = "ab\ncdé f";
+
+    assert_eq!(Span { start: 0, end: 1 }.position(input), (1, 1));
+    assert_eq!(Span { start: 3, end: 4 }.position(input), (2, 1));
+    assert_eq!(Span { start: 8, end: 9 }.position(input), (2, 5));
+}
+
+#[test]
+fn test_cursor() {
+    let lexer = Lexer::new().punctuation(&[':']);
+    let mut cursor = lexer.cursor("Card 7: red 300").unwrap();
+
+    assert!(cursor.peek().unwrap().is_keyword("Card"));
+    assert_eq!(cursor.expect_keyword("Card"), Ok(Span { start: 0, end: 4 }));
+    assert_eq!(cursor.expect_number::<u32>(), Ok(7));
+    assert!(!cursor.eat_punct(';'));
+    assert!(cursor.eat_punct(':'));
+    assert_eq!(cursor.expect_any_keyword(&["red", "blue"]), Ok("red"));
+
+    let err = cursor.expect_number::<u8>().unwrap_err();
+    assert_eq!(err.expected, "a number that fits in u8");
+    assert_eq!(cursor.expect_number::<u16>(), Ok(300));
+
+    assert!(cursor.is_at_end());
+    assert_eq!(cursor.expect_end(), Ok(()));
+    assert_eq!(cursor.next(), None);
+}
+
+#[test]
+fn test_cursor_errors() {
+    let lexer = Lexer::new().punctuation(&[';']);
+    let mut cursor = lexer.cursor("Game x;").unwrap();
+
+    let err = cursor.expect_keyword("Card").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 1, column 1: expected \"Card\" but found \"Game\""
+    );
+
+    cursor.expect_word().unwrap();
+    let err = cursor.expect_any_keyword(&["red", "green"]).unwrap_err();
+    assert_eq!(err.expected, "one of \"red\", \"green\"");
+
+    assert_eq!(cursor.expect_number::<u32>().unwrap_err().column, 6);
+    cursor.expect_word().unwrap();
+    assert_eq!(cursor.expect_end().unwrap_err().found.as_deref(), Some(";"));
+
+    cursor.expect_punct(';').unwrap();
+    let err = cursor.expect_punct(';').unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 1, column 8: expected \";\" but found end of input"
+    );
+}
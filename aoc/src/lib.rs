@@ -0,0 +1,19 @@
+//! Shared building blocks for the daily puzzles, and the `aoc` runner.
+
+pub mod args;
+pub mod day;
+pub mod days;
+pub mod grid;
+pub mod http;
+pub mod interval;
+pub mod lex;
+pub mod log;
+pub mod pattern;
+pub mod puzzle;
+pub mod rand;
+pub mod report;
+pub mod sandbox;
+pub mod scaffold;
+pub mod submit;
+#[cfg(target_os = "linux")]
+pub mod watch;
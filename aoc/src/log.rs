@@ -0,0 +1,254 @@
+//! Levelled diagnostics on stderr, so stdout carries nothing but answers.
+//!
+//! What is shown is picked with `RUST_LOG`: a default level and/or `target=level` pairs,
+//! e.g. `debug`, `day5=trace` or `warn,day4=debug`. Without it only warnings and errors
+//! are shown. Each day names its target with `init`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown log level: {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+/// The most verbose level shown, per target; `None` turns a target off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    default: Option<Level>,
+    targets: Vec<(String, Option<Level>)>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            default: Some(Level::Warn),
+            targets: Vec::new(),
+        }
+    }
+}
+
+fn parse_max(level: &str) -> Option<Option<Level>> {
+    match level {
+        "off" => Some(None),
+        level => level.parse().ok().map(Some),
+    }
+}
+
+impl Filter {
+    /// Parse a `RUST_LOG` style spec; parts that don't parse are skipped.
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Self::default();
+
+        for part in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            match part.split_once('=') {
+                Some((target, level)) => {
+                    if let Some(level) = parse_max(level.trim()) {
+                        filter.targets.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Some(level) = parse_max(part) {
+                        filter.default = level;
+                    }
+                }
+            }
+        }
+
+        filter
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let max = self
+            .targets
+            .iter()
+            .rev()
+            .find(|(name, _)| name == target)
+            .map_or(self.default, |(_, max)| *max);
+
+        max.is_some_and(|max| level <= max)
+    }
+}
+
+struct Logger {
+    target: String,
+    filter: Filter,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| Logger {
+        target: "aoc".to_string(),
+        filter: from_env(),
+    })
+}
+
+fn from_env() -> Filter {
+    std::env::var("RUST_LOG")
+        .map(|spec| Filter::parse(&spec))
+        .unwrap_or_default()
+}
+
+/// Name the target of this process' messages, e.g. `day5`. Only the first call counts.
+pub fn init(target: &str) {
+    let _ = LOGGER.set(Logger {
+        target: target.to_string(),
+        filter: from_env(),
+    });
+}
+
+pub fn enabled(level: Level) -> bool {
+    let logger = logger();
+    logger.filter.enabled(&logger.target, level)
+}
+
+/// Write a message; use the macros, which skip formatting for disabled levels.
+pub fn write(level: Level, args: fmt::Arguments) {
+    eprintln!("[{:<5} {}] {}", level, logger().target, args);
+}
+
+/// The `RUST_LOG` value for `-v` repeated `count` times.
+pub fn verbosity(count: usize) -> Option<&'static str> {
+    match count {
+        0 => None,
+        1 => Some("info"),
+        2 => Some("debug"),
+        _ => Some("trace"),
+    }
+}
+
+/// `RUST_LOG` with `level` as the default, on top of an existing `spec`. The level goes
+/// first, so whatever `spec` sets itself still wins.
+pub fn with_default(level: &str, spec: Option<&str>) -> String {
+    match spec.map(str::trim).filter(|spec| !spec.is_empty()) {
+        Some(spec) => format!("{},{}", level, spec),
+        None => level.to_string(),
+    }
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            $crate::log::write($level, format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[test]
+fn test_default_filter() {
+    let filter = Filter::default();
+
+    assert!(filter.enabled("day1", Level::Error));
+    assert!(filter.enabled("day1", Level::Warn));
+    assert!(!filter.enabled("day1", Level::Info));
+}
+
+#[test]
+fn test_parse_filter() {
+    let filter = Filter::parse("info, day5=trace,day4=off,day3=nonsense");
+
+    assert!(filter.enabled("day1", Level::Info));
+    assert!(!filter.enabled("day1", Level::Debug));
+    assert!(filter.enabled("day5", Level::Trace));
+    assert!(!filter.enabled("day4", Level::Error));
+    assert!(filter.enabled("day3", Level::Info));
+
+    let filter = Filter::parse("DEBUG");
+    assert!(filter.enabled("any", Level::Debug));
+    assert!(!filter.enabled("any", Level::Trace));
+
+    assert!(!Filter::parse("off").enabled("any", Level::Error));
+}
+
+#[test]
+fn test_verbosity() {
+    assert_eq!(verbosity(0), None);
+    assert_eq!(verbosity(1), Some("info"));
+    assert_eq!(verbosity(2), Some("debug"));
+    assert_eq!(verbosity(5), Some("trace"));
+}
+
+#[test]
+fn test_with_default() {
+    assert_eq!(with_default("debug", None), "debug");
+    assert_eq!(with_default("debug", Some(" ")), "debug");
+
+    let filter = Filter::parse(&with_default("debug", Some("day5=trace,day4=off")));
+    assert!(filter.enabled("day1", Level::Debug));
+    assert!(filter.enabled("day5", Level::Trace));
+    assert!(!filter.enabled("day4", Level::Error));
+
+    let filter = Filter::parse(&with_default("debug", Some("error")));
+    assert!(!filter.enabled("day1", Level::Warn));
+}
+
+#[test]
+fn test_level_display() {
+    assert_eq!(format!("[{:<5}]", Level::Warn), "[WARN ]");
+    assert_eq!("Trace".parse(), Ok(Level::Trace));
+    assert!("loud".parse::<Level>().is_err());
+}
@@ -0,0 +1,486 @@
+use aoc::args::Args;
+use aoc::day::{repo_root, Day};
+use aoc::days::DAYS;
+use aoc::http::Client;
+use aoc::puzzle::Puzzle;
+use aoc::report::{self, Format, Record, Status};
+use aoc::sandbox::{self, Limits, Outcome};
+use aoc::scaffold;
+use aoc::submit::{self, Answers, Refusal};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: aoc <command> [options] [-v...]
+
+Commands:
+  new --day N [--year Y]                    Create a day from the templates and register it
+  fetch --day N [--year Y] [--html PATH]    Download the input, and the example and its answers from
+      [--input]                             the puzzle page, or from a saved page at PATH; with
+                                            --input only the input
+  submit --day N [--year Y] --part P        Run a part and post its answer, unless the answers
+                                            recorded in the day's answers.txt rule it out
+  run (--day N [--year Y] | --all)          Run a day, or every registered day
+      [--format text|json|csv]              Print the days' output, or a record per part with
+                                            its answer, timings in ns and status
+      [--timeout SECS] [--memory MIB]       Stop a part after SECS (default 60) or when it uses
+                                            over MIB of memory (default 4096); 0 for no limit
+  watch --day N [--year Y]                  Re-run a day's tests and answers whenever its files change
+  render --day N [--year Y] [--html PATH]   Draw a day's puzzle state, as ANSI or standalone HTML
+  gen --day N [--year Y] [--seed S]         Print a synthetic input; --size scales it (lines, games,
+      [--size N] [day options]              side length, cards or rows per map). Day 3 takes
+                                            --density PERCENT, day 4 --matches W0,W1,...
+                                            (weights per match count), day 5 --span MAX
+
+Options:
+  -v, -vv, -vvv    Show info, debug or trace output of the days on stderr; levels set
+                   in RUST_LOG (e.g. `day5=trace`) are kept
+";
+
+const DEFAULT_YEAR: u16 = 2023;
+
+/// Parts per puzzle; each runs in a process of its own.
+const PARTS: u8 = 2;
+
+/// Seconds a part may run for.
+const DEFAULT_TIMEOUT: u64 = 60;
+
+/// MiB of address space a part may use.
+const DEFAULT_MEMORY: u64 = 4096;
+
+fn main() -> ExitCode {
+    let mut args = Args::from_env();
+    let command = args.subcommand();
+
+    // The days pick their filter up from the environment they inherit, so `-v` adds to
+    // a `RUST_LOG` that is already set rather than replacing it
+    if let Some(level) = aoc::log::verbosity(args.verbosity()) {
+        let spec = std::env::var("RUST_LOG").ok();
+        std::env::set_var("RUST_LOG", aoc::log::with_default(level, spec.as_deref()));
+    }
+
+    match command.as_deref() {
+        Some("new") => new(args),
+        Some("fetch") => fetch(args),
+        Some("run") => run(args),
+        Some("submit") => submit(args),
+        #[cfg(target_os = "linux")]
+        Some("watch") => watch(args),
+        Some("render") => render(args),
+        Some("gen") => gen(args),
+        _ => {
+            eprint!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn day_arg(args: &mut Args) -> Day {
+    let year = args.parse("--year").unwrap_or(DEFAULT_YEAR);
+    let Some(day) = args.parse("--day") else {
+        panic!("Missing --day\n\n{}", USAGE);
+    };
+    Day::new(year, day)
+}
+
+fn new(mut args: Args) -> ExitCode {
+    let day = day_arg(&mut args);
+
+    match scaffold::create(&repo_root(), day) {
+        Ok(written) => {
+            for path in written {
+                println!("Wrote {}", path.display());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to create {}: {}", day, err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Download the day's input unless it is there already, then write the examples of the
+/// puzzle page into the day's directory.
+fn fetch(mut args: Args) -> ExitCode {
+    let root = repo_root();
+    let day = day_arg(&mut args);
+    let client = Client::from_env();
+
+    if !day.dir(&root).is_dir() {
+        eprintln!(
+            "{} not found in {}, create it with aoc new",
+            day,
+            day.dir(&root).display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    if args.flag("--input") {
+        return match fetch_input(&client, &root, day) {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        };
+    }
+
+    // The puzzle page needs no session, so a missing input doesn't hold up the examples
+    let mut input_fetched = true;
+    let puzzle = match args.value("--html") {
+        Some(path) => {
+            let html = std::fs::read_to_string(&path)
+                .unwrap_or_else(|err| panic!("Failed to read {}: {}", path, err));
+            Puzzle::parse(&html)
+        }
+        None => {
+            input_fetched = fetch_input(&client, &root, day);
+
+            match Puzzle::fetch(&client, day) {
+                Ok(puzzle) => puzzle,
+                Err(err) => {
+                    eprintln!("Failed to fetch the puzzle: {}", err);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+    };
+
+    if puzzle.example().is_none() {
+        eprintln!("No example found in the puzzle page");
+        return ExitCode::FAILURE;
+    }
+    for (part, examples) in puzzle
+        .parts
+        .iter()
+        .map(|part| &part.examples)
+        .enumerate()
+        .skip(1)
+    {
+        if examples
+            .first()
+            .is_some_and(|example| Some(example.as_str()) != puzzle.example())
+        {
+            aoc::warn!(
+                "Part {} has an example of its own, only part 1's is written",
+                part + 1
+            );
+        }
+    }
+
+    match puzzle.write(&day.dir(&root)) {
+        Ok(written) => {
+            for path in written {
+                println!("Wrote {}", path.display());
+            }
+            match input_fetched {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            }
+        }
+        Err(err) => {
+            eprintln!("Failed to write the examples: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Download the day's `input.txt`, unless it is already there. False if it isn't there
+/// afterwards.
+fn fetch_input(client: &Client, root: &Path, day: Day) -> bool {
+    let input = day.dir(root).join("input.txt");
+    if input.exists() {
+        println!("Kept {}", input.display());
+        return true;
+    }
+
+    let path = format!("/{}/day/{}/input", day.year, day.day);
+    match client.get_private(&path) {
+        Ok(text) => {
+            std::fs::write(&input, text)
+                .unwrap_or_else(|err| panic!("Failed to write {}: {}", input.display(), err));
+            println!("Wrote {}", input.display());
+            true
+        }
+        Err(err) => {
+            eprintln!("Failed to fetch the input: {}", err);
+            false
+        }
+    }
+}
+
+/// Run one part of a day and submit its answer.
+fn submit(mut args: Args) -> ExitCode {
+    let root = repo_root();
+    let day = day_arg(&mut args);
+    let part = match args.parse::<u8>("--part") {
+        Some(part) if (1..=PARTS).contains(&part) => part,
+        _ => panic!("Missing --part 1 or 2\n\n{}", USAGE),
+    };
+    let limits = limits_arg(&mut args);
+
+    let path = Answers::path(&root, day);
+    let mut answers = Answers::load(&path)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err));
+
+    // Don't spend time on the solution when nothing can be submitted anyway
+    if let Err(refusal @ (Refusal::Cooldown(_) | Refusal::Solved(_))) =
+        answers.check(part, "", submit::now())
+    {
+        eprintln!("Not submitted: {}", refusal);
+        return ExitCode::FAILURE;
+    }
+
+    let records = day_records(&root, day, part..=part, limits, Format::Json);
+    let answer = match records.first() {
+        Some(Record {
+            answer: Some(answer),
+            ..
+        }) => answer.clone(),
+        Some(record) => {
+            eprintln!("{} part {} has no answer: {}", day, part, record.status);
+            return ExitCode::FAILURE;
+        }
+        None => {
+            eprintln!("{} does not solve part {}", day, part);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let client = Client::from_env();
+    match submit::submit(&client, &mut answers, day, part, &answer, submit::now()) {
+        Ok(response) => {
+            println!("{} part {}: {} is {}", day, part, answer, response.outcome);
+            if let Some(wait) = response.wait {
+                println!("Next submission in {}s", wait.as_secs());
+            }
+            if response.outcome == submit::Outcome::Unknown {
+                println!("{}", response.message);
+            }
+
+            if response.outcome == submit::Outcome::Correct {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(err) => {
+            eprintln!("{} part {}: {}", day, part, err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `--timeout SECS` and `--memory MIB`, where 0 is no limit.
+fn limits_arg(args: &mut Args) -> Limits {
+    let timeout = args.parse("--timeout").unwrap_or(DEFAULT_TIMEOUT);
+    let memory = args.parse::<u64>("--memory").unwrap_or(DEFAULT_MEMORY);
+    let Some(bytes) = memory.checked_mul(1 << 20) else {
+        panic!(
+            "Invalid value for --memory: {} MiB does not fit in 64 bits",
+            memory
+        );
+    };
+
+    Limits {
+        timeout: Some(Duration::from_secs(timeout)).filter(|timeout| !timeout.is_zero()),
+        memory: Some(bytes).filter(|&bytes| bytes != 0),
+    }
+}
+
+fn run(mut args: Args) -> ExitCode {
+    let root = repo_root();
+    let format = args.parse("--format").unwrap_or(Format::Text);
+    let limits = limits_arg(&mut args);
+    let days = if args.flag("--all") {
+        DAYS.to_vec()
+    } else {
+        vec![day_arg(&mut args)]
+    };
+
+    match format {
+        Format::Json => print!("["),
+        Format::Csv => println!("{}", report::CSV_HEADER),
+        Format::Text => {}
+    }
+
+    let mut failed = false;
+    let mut first = true;
+    for day in days {
+        if format == Format::Text {
+            println!("== {}", day);
+        }
+
+        for record in day_records(&root, day, 1..=PARTS, limits, format) {
+            failed |= record.status != Status::Ok;
+            match format {
+                Format::Json => {
+                    let separator = if first { "" } else { "," };
+                    print!("{}\n{}", separator, record.to_json());
+                }
+                Format::Csv => println!("{}", record.to_csv()),
+                Format::Text => {}
+            }
+            first = false;
+        }
+    }
+
+    if format == Format::Json {
+        println!("\n]");
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Build a day, then run each of `parts` in a process of its own under `limits`. As
+/// text, the day's own output is passed through and only failures are recorded.
+fn day_records(
+    root: &Path,
+    day: Day,
+    parts: RangeInclusive<u8>,
+    limits: Limits,
+    format: Format,
+) -> Vec<Record> {
+    if !day.exists(root) {
+        eprintln!("{} not found in {}", day, day.dir(root).display());
+        return report::records(day, "", false, parts);
+    }
+
+    // Build outside the sandbox, the limits are meant for the solution alone
+    let built = day.cargo_build(root).status().expect("Failed to run cargo");
+    if !built.success() {
+        return report::records(day, "", false, parts);
+    }
+
+    let mut records = Vec::new();
+    for part in parts {
+        let mut command = day.release_binary(root);
+        command.env(report::PART_ENV, part.to_string());
+        if format != Format::Text {
+            command.env(report::ENV, "1");
+        }
+
+        let finished = sandbox::run(command, limits).expect("Failed to run the day");
+        eprint!("{}", finished.stderr);
+        if format == Format::Text {
+            print!("{}", finished.stdout);
+        }
+
+        let status = match finished.outcome {
+            Outcome::Exited(status) => {
+                let stdout = &finished.stdout;
+                records.extend(report::records(day, stdout, status.success(), part..=part));
+                continue;
+            }
+            Outcome::TimedOut => Status::Timeout,
+            Outcome::OutOfMemory => Status::Oom,
+        };
+        if format == Format::Text {
+            println!("Part {}: {}", part, status.to_string().to_uppercase());
+        }
+        records.push(Record::failed(day, part, status));
+    }
+
+    records
+}
+
+#[cfg(target_os = "linux")]
+fn watch(mut args: Args) -> ExitCode {
+    use aoc::watch::{run_once, Watcher, DEBOUNCE};
+
+    let root = repo_root();
+    let day = day_arg(&mut args);
+
+    if !day.exists(&root) {
+        eprintln!("{} not found in {}", day, day.dir(&root).display());
+        return ExitCode::FAILURE;
+    }
+
+    let mut watcher = Watcher::new().expect("Failed to start inotify");
+    watcher
+        .watch_dir(&day.dir(&root))
+        .and_then(|_| watcher.watch_dir(&day.crate_dir(&root)))
+        .and_then(|_| watcher.watch_tree(&day.crate_dir(&root).join("src")))
+        .expect("Failed to watch the day's files");
+
+    println!("== {}", day);
+    let mut answers = run_once(&root, day, None);
+
+    loop {
+        let changed = watcher.wait(DEBOUNCE).expect("Failed to wait for changes");
+        let names = changed
+            .iter()
+            .map(|path| {
+                path.strip_prefix(day.dir(&root))
+                    .unwrap_or(path)
+                    .display()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+
+        println!();
+        println!("== {}: {} changed", day, names.join(", "));
+        answers = run_once(&root, day, Some(&answers));
+    }
+}
+
+/// Forward to the day's own `render` subcommand.
+fn render(mut args: Args) -> ExitCode {
+    let root = repo_root();
+    let day = day_arg(&mut args);
+
+    if !day.exists(&root) {
+        eprintln!("{} not found in {}", day, day.dir(&root).display());
+        return ExitCode::FAILURE;
+    }
+
+    let mut forward = vec!["render".to_string()];
+    if let Some(path) = args.value("--html") {
+        // The day runs inside its own crate directory
+        let path = std::env::current_dir()
+            .expect("Failed to get current directory")
+            .join(Path::new(&path));
+        forward.push("--html".to_string());
+        forward.push(path.display().to_string());
+    }
+
+    let status = day
+        .cargo_run(&root, &forward)
+        .status()
+        .expect("Failed to run cargo");
+
+    if status.success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Forward to the day's own `gen` subcommand, with every option but the day.
+fn gen(mut args: Args) -> ExitCode {
+    let root = repo_root();
+    let day = day_arg(&mut args);
+
+    if !day.exists(&root) {
+        eprintln!("{} not found in {}", day, day.dir(&root).display());
+        return ExitCode::FAILURE;
+    }
+
+    let mut forward = vec!["gen".to_string()];
+    forward.extend(args.rest().iter().cloned());
+
+    let status = day
+        .cargo_run(&root, &forward)
+        .status()
+        .expect("Failed to run cargo");
+
+    if status.success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
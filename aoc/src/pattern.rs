@@ -0,0 +1,466 @@
+//! Line parsers declared as templates, e.g. `"Card {id:u32}: {win:[u32]} | {have:[u32]}"`.
+//!
+//! A template is literal text with captures in braces:
+//!
+//! * `{name:u32}` a single number of the given integer type,
+//! * `{name:[u32]}` zero or more whitespace separated numbers,
+//! * `{name}` text up to the next literal, or the end of the line.
+//!
+//! Whitespace in the template matches any amount of whitespace, including none.
+//!
+//! Mismatches are reported as `lex::Error`, the same as the cursor in `lex` reports them.
+
+use crate::lex::Error;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Number(&'static str),
+    List(&'static str),
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(String),
+    Space,
+    Capture(String, Kind),
+}
+
+const TYPES: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// Whether `text` is a valid number of integer type `ty`.
+fn fits(ty: &str, text: &str) -> bool {
+    match ty {
+        "u8" => text.parse::<u8>().is_ok(),
+        "u16" => text.parse::<u16>().is_ok(),
+        "u32" => text.parse::<u32>().is_ok(),
+        "u64" => text.parse::<u64>().is_ok(),
+        "u128" => text.parse::<u128>().is_ok(),
+        "usize" => text.parse::<usize>().is_ok(),
+        "i8" => text.parse::<i8>().is_ok(),
+        "i16" => text.parse::<i16>().is_ok(),
+        "i32" => text.parse::<i32>().is_ok(),
+        "i64" => text.parse::<i64>().is_ok(),
+        "i128" => text.parse::<i128>().is_ok(),
+        "isize" => text.parse::<isize>().is_ok(),
+        _ => unreachable!("Unknown type {}", ty),
+    }
+}
+
+fn parse_type(ty: &str) -> &'static str {
+    TYPES
+        .iter()
+        .find(|&&known| known == ty)
+        .unwrap_or_else(|| panic!("Unknown capture type: {}", ty))
+}
+
+/// A captured value and its 1-based column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Field<'a> {
+    pub text: &'a str,
+    pub column: usize,
+}
+
+/// Values captured by a successful match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Captures<'a> {
+    captures: Vec<(String, Vec<Field<'a>>)>,
+}
+
+impl<'a> Captures<'a> {
+    /// Fields of capture `name`; one for numbers and text, any number for lists.
+    pub fn fields(&self, name: &str) -> &[Field<'a>] {
+        self.captures
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, fields)| fields.as_slice())
+            .unwrap_or_else(|| panic!("No capture named {}", name))
+    }
+
+    pub fn str(&self, name: &str) -> &'a str {
+        match self.fields(name) {
+            [field] => field.text,
+            _ => panic!("Capture {} is a list", name),
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> T
+    where
+        T::Err: Debug,
+    {
+        self.str(name).parse().unwrap()
+    }
+
+    pub fn list<T: FromStr>(&self, name: &str) -> Vec<T>
+    where
+        T::Err: Debug,
+    {
+        self.fields(name)
+            .iter()
+            .map(|field| field.text.parse().unwrap())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pieces: Vec<Piece>,
+}
+
+impl Pattern {
+    /// Compile `template`; panics when the template itself is malformed.
+    pub fn new(template: &str) -> Self {
+        let mut pieces = Vec::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                pieces.push(Piece::Space);
+            } else if c == '{' {
+                let mut capture = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => capture.push(c),
+                        None => panic!("Unterminated capture in template: {}", template),
+                    }
+                }
+                let (name, ty) = capture.split_once(':').unwrap_or((&capture, ""));
+
+                let kind = match ty.trim() {
+                    "" | "str" => Kind::Text,
+                    ty if ty.starts_with('[') && ty.ends_with(']') => {
+                        Kind::List(parse_type(&ty[1..ty.len() - 1]))
+                    }
+                    ty => Kind::Number(parse_type(ty)),
+                };
+                pieces.push(Piece::Capture(name.trim().to_string(), kind));
+            } else {
+                let mut literal = c.to_string();
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != '{') {
+                    literal.push(c);
+                }
+                pieces.push(Piece::Literal(literal));
+            }
+        }
+
+        Self { pieces }
+    }
+
+    pub fn parse<'a>(&self, input: &'a str) -> Result<Captures<'a>, Error> {
+        self.parse_line(1, input)
+    }
+
+    /// Match `input`, which is line `line` of a larger text.
+    pub fn parse_line<'a>(&self, line: usize, input: &'a str) -> Result<Captures<'a>, Error> {
+        let mut matcher = Matcher {
+            input,
+            line,
+            pos: 0,
+        };
+        let mut captures = Vec::new();
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let next_literal = self.pieces[i + 1..].iter().find_map(|piece| match piece {
+                Piece::Literal(literal) => Some(literal.as_str()),
+                _ => None,
+            });
+
+            match piece {
+                Piece::Space => matcher.skip_whitespace(),
+                Piece::Literal(literal) => matcher.literal(literal)?,
+                Piece::Capture(name, Kind::Number(ty)) => {
+                    let field = matcher.number(ty, "a number")?;
+                    captures.push((name.clone(), vec![field]));
+                }
+                Piece::Capture(name, Kind::List(ty)) => {
+                    let fields = matcher.list(ty, next_literal)?;
+                    captures.push((name.clone(), fields));
+                }
+                Piece::Capture(name, Kind::Text) => {
+                    let field = matcher.text(next_literal)?;
+                    captures.push((name.clone(), vec![field]));
+                }
+            }
+        }
+
+        matcher.skip_whitespace();
+        if matcher.pos < input.len() {
+            return Err(matcher.error("end of line"));
+        }
+
+        Ok(Captures { captures })
+    }
+
+    /// Match every non-blank line of `text`.
+    pub fn parse_lines<'a, 'p>(
+        &'p self,
+        text: &'a str,
+    ) -> impl Iterator<Item = Result<Captures<'a>, Error>> + 'p
+    where
+        'a: 'p,
+    {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| self.parse_line(index + 1, line))
+    }
+}
+
+struct Matcher<'a> {
+    input: &'a str,
+    line: usize,
+    pos: usize,
+}
+
+impl<'a> Matcher<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn column(&self) -> usize {
+        self.input[..self.pos].chars().count() + 1
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// The token at the current position: a run of letters and digits, or else a single
+    /// character.
+    fn token(&self) -> Option<&'a str> {
+        let rest = self.rest();
+        let first = rest.chars().next()?;
+        let len = rest
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| !c.is_alphanumeric())
+            .map_or(rest.len(), |(i, _)| i);
+
+        if first.is_alphanumeric() || first == '-' {
+            Some(&rest[..len])
+        } else {
+            Some(&rest[..first.len_utf8()])
+        }
+    }
+
+    fn error(&self, expected: &str) -> Error {
+        Error {
+            line: self.line,
+            column: self.column(),
+            expected: expected.to_string(),
+            found: self.token().map(str::to_string),
+        }
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), Error> {
+        self.skip_whitespace();
+        if !self.rest().starts_with(literal) {
+            return Err(self.error(&format!("{:?}", literal)));
+        }
+        self.pos += literal.len();
+        Ok(())
+    }
+
+    fn number(&mut self, ty: &str, expected: &str) -> Result<Field<'a>, Error> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let len = sign + rest[sign..].len()
+            - rest[sign..]
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+
+        if len == sign {
+            return Err(self.error(expected));
+        }
+        if !fits(ty, &rest[..len]) {
+            return Err(self.error(&format!("a number that fits in {}", ty)));
+        }
+
+        let field = Field {
+            text: &rest[..len],
+            column: self.column(),
+        };
+        self.pos += len;
+        Ok(field)
+    }
+
+    fn list(&mut self, ty: &str, until: Option<&str>) -> Result<Vec<Field<'a>>, Error> {
+        let expected = match until {
+            Some(literal) => format!("a number or {:?}", literal),
+            None => "a number".to_string(),
+        };
+
+        let mut fields = Vec::new();
+        loop {
+            let before = self.pos;
+            self.skip_whitespace();
+
+            let rest = self.rest();
+            if rest.is_empty() || until.is_some_and(|literal| rest.starts_with(literal)) {
+                // Leave the whitespace for whatever follows
+                self.pos = before;
+                return Ok(fields);
+            }
+
+            fields.push(self.number(ty, &expected)?);
+        }
+    }
+
+    fn text(&mut self, until: Option<&str>) -> Result<Field<'a>, Error> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = match until {
+            Some(literal) => rest.find(literal).unwrap_or(rest.len()),
+            None => rest.len(),
+        };
+
+        let text = rest[..len].trim_end();
+        if text.is_empty() {
+            return Err(self.error("text"));
+        }
+
+        let field = Field {
+            text,
+            column: self.column(),
+        };
+        self.pos += text.len();
+        Ok(field)
+    }
+}
+
+#[test]
+fn test_compile() {
+    let pattern = Pattern::new("Card {id:u32}:  {win:[u8]} | {name}");
+
+    assert_eq!(
+        pattern.pieces,
+        [
+            Piece::Literal("Card".to_string()),
+            Piece::Space,
+            Piece::Capture("id".to_string(), Kind::Number("u32")),
+            Piece::Literal(":".to_string()),
+            Piece::Space,
+            Piece::Capture("win".to_string(), Kind::List("u8")),
+            Piece::Space,
+            Piece::Literal("|".to_string()),
+            Piece::Space,
+            Piece::Capture("name".to_string(), Kind::Text),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unknown capture type: f32")]
+fn test_compile_unknown_type() {
+    Pattern::new("{x:f32}");
+}
+
+#[test]
+#[should_panic(expected = "Unterminated capture in template: Card {id:u32")]
+fn test_compile_unterminated_capture() {
+    Pattern::new("Card {id:u32");
+}
+
+#[test]
+fn test_parse() {
+    let pattern = Pattern::new("Card {id:u32}: {win:[u8]} | {have:[u8]}");
+    let captures = pattern.parse("Card  12:41 48 | 83  6 ").unwrap();
+
+    assert_eq!(captures.get::<u32>("id"), 12);
+    assert_eq!(captures.list::<u8>("win"), [41, 48]);
+    assert_eq!(captures.list::<u8>("have"), [83, 6]);
+    assert_eq!(
+        captures.fields("have"),
+        [
+            Field {
+                text: "83",
+                column: 18
+            },
+            Field {
+                text: "6",
+                column: 22
+            }
+        ]
+    );
+
+    let captures = pattern.parse("Card 1: | ").unwrap();
+    assert!(captures.list::<u8>("win").is_empty());
+}
+
+#[test]
+fn test_parse_text() {
+    let pattern = Pattern::new("{name}: {cells:[i64]}");
+
+    let captures = pattern.parse("seed-to-soil map:").unwrap();
+    assert_eq!(captures.str("name"), "seed-to-soil map");
+    assert!(captures.list::<i64>("cells").is_empty());
+
+    let captures = pattern.parse("seeds: 79 -14").unwrap();
+    assert_eq!(captures.str("name"), "seeds");
+    assert_eq!(captures.list::<i64>("cells"), [79, -14]);
+
+    let err = pattern.parse("50 98 2").unwrap_err();
+    assert_eq!((err.column, err.found), (8, None));
+}
+
+#[test]
+fn test_parse_errors() {
+    let pattern = Pattern::new("Card {id:u32}: {win:[u8]} | {have:[u8]}");
+
+    #[rustfmt::skip]
+    let cases = [
+        ("Game 1: 1 | 2",   1,  Some("Game"), "\"Card\""),
+        ("Card x: 1 | 2",   6,  Some("x"),    "a number"),
+        ("Card -1: 1 | 2",  6,  Some("-1"),   "a number that fits in u32"),
+        ("Card 1 1 | 2",    8,  Some("1"),    "\":\""),
+        ("Card 1: 1 2",     12, None,         "\"|\""),
+        ("Card 1: 1 a | 2", 11, Some("a"),    "a number or \"|\""),
+        ("Card 1: 256 | 2", 9,  Some("256"),  "a number that fits in u8"),
+        ("Card 1: 1 | 2 |", 15, Some("|"),    "a number"),
+    ];
+
+    for (input, column, found, expected) in cases {
+        let err = pattern.parse_line(3, input).unwrap_err();
+        assert_eq!(
+            err,
+            Error {
+                line: 3,
+                column,
+                expected: expected.to_string(),
+                found: found.map(str::to_string),
+            },
+            "Input: {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_parse_end_of_line() {
+    let pattern = Pattern::new("Game {id:u8}");
+
+    assert!(pattern.parse("Game 1  ").is_ok());
+    let err = pattern.parse("Game 1 x").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 1, column 8: expected end of line but found \"x\""
+    );
+}
+
+#[test]
+fn test_parse_lines() {
+    let pattern = Pattern::new("{n:u8}");
+    let lines = pattern.parse_lines("1\r\n\n2\nx\n").collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1].as_ref().unwrap().get::<u8>("n"), 2);
+    assert_eq!(lines[2].as_ref().unwrap_err().line, 4);
+}
@@ -0,0 +1,217 @@
+//! Examples and their answers, taken from a puzzle page.
+//!
+//! Each part of a puzzle is an `<article>`. Its examples are the `<pre><code>` blocks, and
+//! its answer for the example is the last emphasised code, `<code><em>..</em></code>`.
+
+use crate::day::Day;
+use crate::http::{Client, HttpError};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Part {
+    pub examples: Vec<String>,
+    pub answer: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Puzzle {
+    /// Only the first part until it is solved.
+    pub parts: Vec<Part>,
+}
+
+/// Text between `open` and `close`, for every non-overlapping occurrence in `html`.
+fn between<'a>(html: &'a str, open: &str, close: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(close) else {
+            break;
+        };
+        found.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    found
+}
+
+/// Text content of an HTML fragment: tags dropped, entities decoded.
+pub(crate) fn text(html: &str) -> String {
+    let mut without_tags = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => without_tags.push(c),
+            _ => {}
+        }
+    }
+
+    // `&amp;` goes last, so `&amp;lt;` comes out as `&lt;`
+    without_tags
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// The last emphasised code of an article, either `<code><em>` or `<em><code>`.
+fn answer(article: &str) -> Option<String> {
+    ["<code><em>", "<em><code>"]
+        .iter()
+        .filter_map(|open| {
+            let at = article.rfind(open)?;
+            let close = if open.starts_with("<code>") {
+                "</em></code>"
+            } else {
+                "</code></em>"
+            };
+            let answer = between(&article[at..], open, close).pop()?;
+            Some((at, text(answer)))
+        })
+        .max_by_key(|(at, _)| *at)
+        .map(|(_, answer)| answer)
+}
+
+impl Puzzle {
+    pub fn parse(html: &str) -> Self {
+        let parts = between(html, "<article", "</article>")
+            .into_iter()
+            .map(|article| Part {
+                examples: between(article, "<pre><code>", "</code></pre>")
+                    .into_iter()
+                    .map(text)
+                    .collect(),
+                answer: answer(article),
+            })
+            .collect();
+
+        Self { parts }
+    }
+
+    /// The example the parts are explained with: the first one of part 1.
+    pub fn example(&self) -> Option<&str> {
+        self.parts.first()?.examples.first().map(String::as_str)
+    }
+
+    /// One answer per line, as the day's tests read them from `expected.txt`.
+    pub fn expected(&self) -> String {
+        self.parts
+            .iter()
+            .map_while(|part| part.answer.as_ref())
+            .map(|answer| format!("{}\n", answer))
+            .collect()
+    }
+
+    /// Fetch and parse the day's puzzle page. With a session it includes part 2 once
+    /// part 1 is solved.
+    pub fn fetch(client: &Client, day: Day) -> Result<Self, HttpError> {
+        let html = client.get(&format!("/{}/day/{}", day.year, day.day))?;
+        Ok(Self::parse(&html))
+    }
+
+    /// Write `example.txt` and `expected.txt` into `dir`, returning the files written.
+    pub fn write(&self, dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+
+        if let Some(example) = self.example() {
+            let path = dir.join("example.txt");
+            std::fs::write(&path, example)?;
+            written.push(path);
+        }
+
+        let expected = self.expected();
+        if !expected.is_empty() {
+            let path = dir.join("expected.txt");
+            std::fs::write(&path, expected)?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+/// Trimmed down from a real puzzle page, with part 1 solved.
+#[cfg(test)]
+const PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en-us">
+<head><title>Day 2 - Advent of Code 2023</title></head>
+<body>
+<main>
+<article class="day-desc"><h2>--- Day 2: Cube Conundrum ---</h2>
+<p>For example, the record of a few games might look like this:</p>
+<pre><code>Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, <em>20</em> red; 5 blue, 4 red, 13 green; 5 green, 1 red
+</code></pre>
+<p>The Elf would first like to know which games would have been possible if the bag contained <em>only 12 red cubes, 13 green cubes, and 14 blue</em>?</p>
+<p>In the example above, games 1 and 2 would have been <em>possible</em>. If you add up the IDs of the games that would have been possible, you get <code><em>3</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>2771</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>In game 1, the game could have been played with as few as 4 red, 2 green, and 6 blue cubes (<code>4 &lt; 5</code>).</p>
+<p>Adding up these five powers produces the sum <em><code>2286</code></em>.</p>
+</article>
+</main>
+</body>
+</html>
+"#;
+
+#[test]
+fn test_parse() {
+    let puzzle = Puzzle::parse(PAGE);
+
+    assert_eq!(puzzle.parts.len(), 2);
+    assert_eq!(puzzle.parts[0].examples.len(), 1);
+    assert!(puzzle.parts[1].examples.is_empty());
+    assert_eq!(puzzle.parts[0].answer.as_deref(), Some("3"));
+    assert_eq!(puzzle.parts[1].answer.as_deref(), Some("2286"));
+
+    let example = puzzle.example().unwrap();
+    assert_eq!(example.lines().count(), 3);
+    assert!(example.ends_with("5 green, 1 red\n"));
+    assert!(example.contains("6 blue, 20 red;"));
+    assert_eq!(puzzle.expected(), "3\n2286\n");
+}
+
+#[test]
+fn test_parse_unsolved() {
+    let page = PAGE.split("<p>Your puzzle answer").next().unwrap();
+    let puzzle = Puzzle::parse(page);
+
+    assert_eq!(puzzle.parts.len(), 1);
+    assert_eq!(puzzle.expected(), "3\n");
+    assert_eq!(Puzzle::parse("<html></html>"), Puzzle::default());
+}
+
+#[test]
+fn test_text() {
+    assert_eq!(
+        text("a &lt;<em>b</em>&gt; &amp;lt; &quot;c&quot;"),
+        "a <b> &lt; \"c\""
+    );
+}
+
+#[test]
+fn test_fetch_and_write() {
+    let (base_url, server) = crate::http::stub_server(vec![(200, PAGE.to_string())]);
+    let client = Client::new(&base_url, None);
+
+    let puzzle = Puzzle::fetch(&client, Day::new(2023, 2)).unwrap();
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /2023/day/2 HTTP/1.1"));
+
+    let dir = std::env::temp_dir().join(format!("aoc-puzzle-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let written = puzzle.write(&dir).unwrap();
+
+    assert_eq!(written, [dir.join("example.txt"), dir.join("expected.txt")]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("expected.txt")).unwrap(),
+        "3\n2286\n"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
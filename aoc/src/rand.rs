@@ -0,0 +1,103 @@
+use std::ops::Range;
+
+/// Small seeded pseudo random generator (SplitMix64).
+///
+/// Not suitable for anything but generating test inputs; the point is that the same seed
+/// always gives the same sequence, so failures can be reproduced.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "Empty range");
+        // Multiply-shift keeps the bias negligible for the small ranges used here
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// Uniform value in `range`.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        range.start + self.below(range.end - range.start)
+    }
+
+    /// True with probability `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[test]
+fn test_deterministic() {
+    let a = (0..5).map(|_| Rng::new(42).next_u64()).collect::<Vec<_>>();
+    assert!(a.iter().all(|&x| x == a[0]));
+
+    let mut rng = Rng::new(42);
+    let first = (0..5).map(|_| rng.next_u64()).collect::<Vec<_>>();
+    let mut rng = Rng::new(42);
+    let second = (0..5).map(|_| rng.next_u64()).collect::<Vec<_>>();
+    assert_eq!(first, second);
+
+    assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+}
+
+#[test]
+fn test_ranges() {
+    let mut rng = Rng::new(7);
+    let mut seen = [false; 6];
+
+    for _ in 0..1000 {
+        let value = rng.range(10..16);
+        assert!((10..16).contains(&value));
+        seen[(value - 10) as usize] = true;
+    }
+
+    assert!(seen.iter().all(|&s| s), "Not every value was produced");
+}
+
+#[test]
+fn test_chance() {
+    let mut rng = Rng::new(3);
+
+    assert!((0..100).all(|_| !rng.chance(0, 4)));
+    assert!((0..100).all(|_| rng.chance(4, 4)));
+
+    let hits = (0..10_000).filter(|_| rng.chance(1, 4)).count();
+    assert!((2_000..3_000).contains(&hits), "Hits: {}", hits);
+}
+
+#[test]
+fn test_shuffle() {
+    let mut rng = Rng::new(11);
+    let mut items = (0..20).collect::<Vec<_>>();
+
+    rng.shuffle(&mut items);
+    assert_ne!(items, (0..20).collect::<Vec<_>>());
+
+    items.sort();
+    assert_eq!(items, (0..20).collect::<Vec<_>>());
+}
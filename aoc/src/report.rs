@@ -0,0 +1,370 @@
+//! Answers with their timings, for the runner's `--format json` and `--format csv`.
+//!
+//! A day solves through a `Report`. Run by hand it prints `label: answer` lines as
+//! before; with `AOC_REPORT` set by the runner it prints one tab separated line per part
+//! instead, which the runner turns into `Record`s.
+
+use crate::day::Day;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Environment variable asking a day for machine-readable output.
+pub const ENV: &str = "AOC_REPORT";
+
+/// Environment variable restricting a day to a single part.
+pub const PART_ENV: &str = "AOC_PART";
+
+/// First field of every machine-readable line, so they stand out from other output.
+const PREFIX: &str = "aoc-report";
+
+/// Times a day's parsing and parts, and prints their answers.
+#[derive(Debug, Default)]
+pub struct Report {
+    machine: bool,
+    only: Option<u8>,
+    parse_ns: u64,
+}
+
+impl Report {
+    pub fn from_env() -> Self {
+        Self {
+            machine: std::env::var_os(ENV).is_some(),
+            only: std::env::var(PART_ENV)
+                .ok()
+                .and_then(|part| part.parse().ok()),
+            parse_ns: 0,
+        }
+    }
+
+    /// Run and time the parsing shared by all parts.
+    pub fn parse<T>(&mut self, parse: impl FnOnce() -> T) -> T {
+        let (parsed, ns) = timed(parse);
+        crate::info!("Parsed in {:?}", Duration::from_nanos(ns));
+        self.parse_ns = ns;
+        parsed
+    }
+
+    /// Run and time a part, then print its answer. Skipped if `AOC_PART` names another.
+    pub fn part<A: fmt::Display>(&mut self, part: u8, label: &str, solve: impl FnOnce() -> A) {
+        if self.only.is_some_and(|only| only != part) {
+            return;
+        }
+
+        let (answer, solve_ns) = timed(solve);
+        crate::info!(
+            "Part {} solved in {:?}",
+            part,
+            Duration::from_nanos(solve_ns)
+        );
+
+        if self.machine {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                PREFIX,
+                part,
+                self.parse_ns,
+                solve_ns,
+                escape(&answer.to_string())
+            );
+        } else {
+            println!("{}: {}", label, answer);
+        }
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed().as_nanos() as u64)
+}
+
+/// Keep an answer on a single line; some answers are drawn over several.
+fn escape(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(answer: &str) -> String {
+    let mut unescaped = String::with_capacity(answer.len());
+    let mut chars = answer.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// The day failed to build, panicked or exited unsuccessfully before answering.
+    Error,
+    /// Killed by the runner for running too long.
+    Timeout,
+    /// Ran out of the memory the runner allowed it.
+    Oom,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "ok"),
+            Status::Error => write!(f, "error"),
+            Status::Timeout => write!(f, "timeout"),
+            Status::Oom => write!(f, "oom"),
+        }
+    }
+}
+
+/// The outcome of one part of one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub day: Day,
+    pub part: u8,
+    pub answer: Option<String>,
+    pub parse_ns: Option<u64>,
+    pub solve_ns: Option<u64>,
+    pub status: Status,
+}
+
+impl Record {
+    pub fn failed(day: Day, part: u8, status: Status) -> Self {
+        Self {
+            day,
+            part,
+            answer: None,
+            parse_ns: None,
+            solve_ns: None,
+            status,
+        }
+    }
+
+    /// Parse a machine-readable line of the day's output.
+    fn parse(day: Day, line: &str) -> Option<Self> {
+        let mut fields = line
+            .strip_prefix(PREFIX)?
+            .strip_prefix('\t')?
+            .splitn(4, '\t');
+
+        Some(Self {
+            day,
+            part: fields.next()?.parse().ok()?,
+            parse_ns: Some(fields.next()?.parse().ok()?),
+            solve_ns: Some(fields.next()?.parse().ok()?),
+            answer: Some(unescape(fields.next()?)),
+            status: Status::Ok,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        fn or_null<T: ToString>(value: Option<T>) -> String {
+            value.map_or("null".to_string(), |value| value.to_string())
+        }
+
+        format!(
+            r#"{{"year": {}, "day": {}, "part": {}, "answer": {}, "parse_ns": {}, "solve_ns": {}, "status": "{}"}}"#,
+            self.day.year,
+            self.day.day,
+            self.part,
+            or_null(self.answer.as_deref().map(json_string)),
+            or_null(self.parse_ns),
+            or_null(self.solve_ns),
+            self.status
+        )
+    }
+
+    /// A row below `CSV_HEADER`; missing values are left empty.
+    pub fn to_csv(&self) -> String {
+        fn or_empty<T: ToString>(value: Option<T>) -> String {
+            value.map_or(String::new(), |value| value.to_string())
+        }
+
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.day.year,
+            self.day.day,
+            self.part,
+            or_empty(self.answer.as_deref().map(csv_field)),
+            or_empty(self.parse_ns),
+            or_empty(self.solve_ns),
+            self.status
+        )
+    }
+}
+
+pub const CSV_HEADER: &str = "year,day,part,answer,parse_ns,solve_ns,status";
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Records for the parts a day reported on `stdout`. If the day did not succeed, every
+/// part of `parts` that it did not get to is recorded as an error.
+pub fn records(day: Day, stdout: &str, success: bool, parts: RangeInclusive<u8>) -> Vec<Record> {
+    let mut records = stdout
+        .lines()
+        .filter_map(|line| Record::parse(day, line))
+        .collect::<Vec<_>>();
+
+    if !success {
+        for part in parts {
+            if !records.iter().any(|record| record.part == part) {
+                records.push(Record::failed(day, part, Status::Error));
+            }
+        }
+        records.sort_by_key(|record| record.part);
+    }
+
+    records
+}
+
+/// How the runner prints results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The days' own output, as is.
+    Text,
+    /// A JSON array, one record per line.
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!(
+                "Unknown format {:?}, expected text, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+const DAY: Day = Day::new(2023, 4);
+
+#[test]
+fn test_escape() {
+    for answer in ["", "42", "a\\nb", "#..#\n#..#\n", "\\"] {
+        assert_eq!(unescape(&escape(answer)), answer);
+        assert!(!escape(answer).contains('\n'));
+    }
+}
+
+#[test]
+fn test_records() {
+    let stdout = "Building...\naoc-report\t1\t120\t3400\t21485\naoc-report\t2\t120\t51\t11024379\n";
+    let records = records(DAY, stdout, true, 1..=2);
+
+    assert_eq!(
+        records,
+        [
+            Record {
+                day: DAY,
+                part: 1,
+                answer: Some("21485".to_string()),
+                parse_ns: Some(120),
+                solve_ns: Some(3400),
+                status: Status::Ok,
+            },
+            Record {
+                day: DAY,
+                part: 2,
+                answer: Some("11024379".to_string()),
+                parse_ns: Some(120),
+                solve_ns: Some(51),
+                status: Status::Ok,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_records_of_failed_day() {
+    let failed = records(DAY, "aoc-report\t1\t5\t6\t13\n", false, 1..=2);
+
+    assert_eq!(failed.len(), 2);
+    assert_eq!(failed[0].status, Status::Ok);
+    assert_eq!(failed[1], Record::failed(DAY, 2, Status::Error));
+
+    // A day that only answers part 2 is not padded when it succeeds
+    assert_eq!(
+        records(DAY, "aoc-report\t2\t5\t6\t13\n", true, 1..=2).len(),
+        1
+    );
+    assert_eq!(
+        records(DAY, "", false, 2..=2),
+        [Record::failed(DAY, 2, Status::Error)]
+    );
+}
+
+#[test]
+fn test_to_json() {
+    let mut record = Record::failed(DAY, 2, Status::Error);
+    assert_eq!(
+        record.to_json(),
+        r#"{"year": 2023, "day": 4, "part": 2, "answer": null, "parse_ns": null, "solve_ns": null, "status": "error"}"#
+    );
+
+    record.answer = Some("say \"hi\"\n".to_string());
+    record.parse_ns = Some(10);
+    record.solve_ns = Some(20);
+    record.status = Status::Ok;
+    assert_eq!(
+        record.to_json(),
+        r#"{"year": 2023, "day": 4, "part": 2, "answer": "say \"hi\"\n", "parse_ns": 10, "solve_ns": 20, "status": "ok"}"#
+    );
+}
+
+#[test]
+fn test_to_csv() {
+    let mut record = Record::failed(DAY, 1, Status::Error);
+    assert_eq!(record.to_csv(), "2023,4,1,,,,error");
+
+    record.answer = Some("a,\"b\"".to_string());
+    record.parse_ns = Some(10);
+    record.solve_ns = Some(20);
+    record.status = Status::Ok;
+    assert_eq!(record.to_csv(), "2023,4,1,\"a,\"\"b\"\"\",10,20,ok");
+    assert_eq!(CSV_HEADER.split(',').count(), 7);
+}
+
+#[test]
+fn test_format() {
+    assert_eq!("json".parse(), Ok(Format::Json));
+    assert_eq!("csv".parse(), Ok(Format::Csv));
+    assert!("yaml".parse::<Format>().is_err());
+}
@@ -0,0 +1,269 @@
+//! Run a solution in a child process under a wall-clock timeout and a memory limit, so
+//! one runaway day can't stall `aoc run --all`.
+//!
+//! The memory limit is an address space rlimit, set between fork and exec with a direct
+//! `setrlimit` call. It is only applied on Linux.
+
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::os::raw::{c_int, c_ulong};
+
+    /// Numbered differently on MIPS only.
+    #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+    pub const RLIMIT_AS: c_int = 6;
+    #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+    pub const RLIMIT_AS: c_int = 9;
+
+    /// As wide as a pointer, which is what `setrlimit` takes, as opposed to `setrlimit64`.
+    #[allow(non_camel_case_types)]
+    pub type rlim_t = c_ulong;
+
+    #[repr(C)]
+    pub struct Rlimit {
+        pub rlim_cur: rlim_t,
+        pub rlim_max: rlim_t,
+    }
+
+    extern "C" {
+        pub fn setrlimit(resource: c_int, rlim: *const Rlimit) -> c_int;
+    }
+}
+
+/// How often a running child is checked on.
+const POLL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    pub timeout: Option<Duration>,
+    /// Bytes of address space.
+    pub memory: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    Exited(ExitStatus),
+    /// Killed after running into the timeout.
+    TimedOut,
+    /// Failed to allocate within the memory limit.
+    OutOfMemory,
+}
+
+/// A finished child, with everything it wrote.
+#[derive(Debug)]
+pub struct Finished {
+    pub outcome: Outcome,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run `command` to completion, or until it hits `limits`. Its stdout and stderr are
+/// captured.
+pub fn run(mut command: Command, limits: Limits) -> io::Result<Finished> {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    if let Some(bytes) = limits.memory {
+        limit_memory(&mut command, bytes);
+    }
+
+    let start = Instant::now();
+    let mut child = command.spawn()?;
+    // Drain both pipes while waiting, or a chatty child blocks on a full pipe
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = wait(&mut child, start, limits.timeout)?;
+
+    let stdout = stdout.join().expect("Failed to read stdout");
+    let stderr = stderr.join().expect("Failed to read stderr");
+    let outcome = match status {
+        None => Outcome::TimedOut,
+        Some(status) if limits.memory.is_some() && is_out_of_memory(status, &stderr) => {
+            Outcome::OutOfMemory
+        }
+        Some(status) => Outcome::Exited(status),
+    };
+
+    Ok(Finished {
+        outcome,
+        stdout,
+        stderr,
+    })
+}
+
+/// Wait for the child, killing it once `timeout` has passed. `None` if it was killed.
+fn wait(
+    child: &mut Child,
+    start: Instant,
+    timeout: Option<Duration>,
+) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+
+        thread::sleep(POLL);
+    }
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
+
+/// Whether a child that ran under a memory limit failed for lack of memory.
+///
+/// Only a child killed by a signal counts: Rust aborts when an allocation fails, and a
+/// failed `mmap` tends to end in a segfault. Stderr is just a hint, to tell a stack
+/// overflow from those and to explain any other signal. A child that exits, panics
+/// included, never ran out, whatever its output says.
+fn is_out_of_memory(status: ExitStatus, stderr: &str) -> bool {
+    let says = |messages: &[&str]| messages.iter().any(|message| stderr.contains(message));
+
+    match signal(status) {
+        Some(SIGABRT | SIGSEGV) => !says(&["has overflowed its stack"]),
+        Some(_) => says(&[
+            "memory allocation of",
+            "out of memory",
+            "Cannot allocate memory",
+        ]),
+        None => false,
+    }
+}
+
+/// The same number on every Unix.
+const SIGABRT: i32 = 6;
+const SIGSEGV: i32 = 11;
+
+#[cfg(unix)]
+fn signal(status: ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn signal(_status: ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn limit_memory(command: &mut Command, bytes: u64) {
+    use std::os::unix::process::CommandExt;
+
+    // Beyond what a 32-bit limit holds is as good as no limit
+    let bytes = sys::rlim_t::try_from(bytes).unwrap_or(sys::rlim_t::MAX);
+    let limit = sys::Rlimit {
+        rlim_cur: bytes,
+        rlim_max: bytes,
+    };
+    // SAFETY: only setrlimit runs between fork and exec, which is async-signal-safe
+    unsafe {
+        command.pre_exec(move || {
+            if sys::setrlimit(sys::RLIMIT_AS, &limit) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn limit_memory(_command: &mut Command, _bytes: u64) {
+    crate::warn!("Memory limits are only supported on Linux, running without");
+}
+
+#[cfg(test)]
+fn sh(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    command
+}
+
+#[test]
+fn test_run_captures_output() {
+    let finished = run(sh("echo answer; echo oops >&2; exit 3"), Limits::default()).unwrap();
+
+    assert!(matches!(finished.outcome, Outcome::Exited(status) if status.code() == Some(3)));
+    assert_eq!(finished.stdout, "answer\n");
+    assert_eq!(finished.stderr, "oops\n");
+}
+
+#[test]
+fn test_run_times_out() {
+    let limits = Limits {
+        timeout: Some(Duration::from_millis(100)),
+        memory: None,
+    };
+
+    let start = Instant::now();
+    let finished = run(sh("echo started; exec sleep 10"), limits).unwrap();
+
+    assert!(matches!(finished.outcome, Outcome::TimedOut));
+    assert_eq!(finished.stdout, "started\n");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_run_limits_memory() {
+    let limits = Limits {
+        timeout: None,
+        memory: Some(64 << 20),
+    };
+
+    // The shell reports its own address space limit in KiB
+    let finished = run(sh("ulimit -v"), limits).unwrap();
+    assert_eq!(finished.stdout.trim(), (64 << 10).to_string());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_is_out_of_memory() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let killed = |signal| ExitStatus::from_raw(signal);
+    let exited = |code| ExitStatus::from_raw(code << 8);
+
+    #[rustfmt::skip]
+    let cases = [
+        (killed(SIGABRT),  "memory allocation of 4294967296 bytes failed\n", true),
+        (killed(SIGABRT),  "",                                                true),
+        (killed(SIGSEGV),  "",                                                true),
+        (killed(SIGABRT),  "thread 'main' has overflowed its stack\n",       false),
+        (killed(15),       "",                                                false),
+        (killed(15),       "sort: Cannot allocate memory\n",                 true),
+        (exited(101),      "thread 'main' panicked at 'out of memory'\n",    false),
+        (exited(1),        "sort: Cannot allocate memory\n",                 false),
+        (exited(0),        "",                                                false),
+    ];
+
+    for (status, stderr, expected) in cases {
+        assert_eq!(
+            is_out_of_memory(status, stderr),
+            expected,
+            "{:?}: {:?}",
+            status,
+            stderr
+        );
+    }
+
+    let aborted = run(sh("kill -ABRT $$"), Limits::default()).unwrap();
+    let Outcome::Exited(status) = aborted.outcome else {
+        panic!("Expected an exit, got {:?}", aborted.outcome);
+    };
+    assert!(is_out_of_memory(status, &aborted.stderr));
+}
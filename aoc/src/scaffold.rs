@@ -0,0 +1,193 @@
+//! `aoc new`: a new day's directory, generated from the templates in `aoc/templates`.
+
+use crate::day::Day;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Line in `aoc/src/days.rs` that new days are inserted above.
+pub const MARKER: &str = "// aoc new: register days above this line";
+
+/// Path relative to the day directory, and template.
+const FILES: [(&str, &str); 7] = [
+    ("Makefile", include_str!("../templates/Makefile.tmpl")),
+    (".gitignore", include_str!("../templates/gitignore.tmpl")),
+    ("example.txt", ""),
+    ("expected.txt", ""),
+    (
+        "rust/.gitignore",
+        include_str!("../templates/crate-gitignore.tmpl"),
+    ),
+    (
+        "rust/Cargo.toml",
+        include_str!("../templates/Cargo.toml.tmpl"),
+    ),
+    (
+        "rust/src/main.rs",
+        include_str!("../templates/main.rs.tmpl"),
+    ),
+];
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// The day directory is already there; nothing was written.
+    Exists(PathBuf),
+    /// The registry has no marker line to insert the day at.
+    MissingMarker(PathBuf),
+    Io(PathBuf, std::io::Error),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScaffoldError::Exists(path) => {
+                write!(f, "{} already exists, not overwriting", path.display())
+            }
+            ScaffoldError::MissingMarker(path) => {
+                write!(f, "{} has no line {:?}", path.display(), MARKER)
+            }
+            ScaffoldError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for ScaffoldError {}
+
+fn fill(template: &str, day: Day) -> String {
+    template
+        .replace("{year}", &day.year.to_string())
+        .replace("{day}", &day.day.to_string())
+}
+
+/// The day's registry file, `<root>/aoc/src/days.rs`.
+pub fn registry(root: &Path) -> PathBuf {
+    root.join("aoc").join("src").join("days.rs")
+}
+
+/// Add `day` to the registry text above the marker, unless it is listed already.
+fn register(source: &str, day: Day) -> Option<String> {
+    let entry = format!("Day::new({}, {}),", day.year, day.day);
+    if source.contains(&entry) {
+        return Some(source.to_string());
+    }
+
+    let at = source.find(MARKER)?;
+    let line_start = source[..at].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &source[line_start..at];
+
+    let mut registered = source.to_string();
+    registered.insert_str(line_start, &format!("{}{}\n", indent, entry));
+    Some(registered)
+}
+
+/// Create the day directory from the templates and register the day. Returns the
+/// files written.
+pub fn create(root: &Path, day: Day) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let dir = day.dir(root);
+    if dir.exists() {
+        return Err(ScaffoldError::Exists(dir));
+    }
+
+    // Check the registry first so a failure leaves nothing half created
+    let registry = registry(root);
+    let source = std::fs::read_to_string(&registry)
+        .map_err(|err| ScaffoldError::Io(registry.clone(), err))?;
+    let registered =
+        register(&source, day).ok_or_else(|| ScaffoldError::MissingMarker(registry.clone()))?;
+
+    let mut written = Vec::new();
+    for (name, template) in FILES {
+        let path = dir.join(name);
+        let parent = path.parent().expect("Template path has no parent");
+        std::fs::create_dir_all(parent).map_err(|err| ScaffoldError::Io(parent.into(), err))?;
+        std::fs::write(&path, fill(template, day))
+            .map_err(|err| ScaffoldError::Io(path.clone(), err))?;
+        written.push(path);
+    }
+
+    std::fs::write(&registry, registered)
+        .map_err(|err| ScaffoldError::Io(registry.clone(), err))?;
+    written.push(registry);
+
+    Ok(written)
+}
+
+/// Empty scratch directory for a test, unique per process and `name`.
+#[cfg(test)]
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_register() {
+    let source = format!(
+        "const DAYS = &[\n    Day::new(2023, 1),\n    {}\n];\n",
+        MARKER
+    );
+
+    let registered = register(&source, Day::new(2023, 2)).unwrap();
+    assert_eq!(
+        registered,
+        format!(
+            "const DAYS = &[\n    Day::new(2023, 1),\n    Day::new(2023, 2),\n    {}\n];\n",
+            MARKER
+        )
+    );
+
+    assert_eq!(register(&registered, Day::new(2023, 2)), Some(registered));
+    assert_eq!(register("const DAYS = &[];", Day::new(2023, 2)), None);
+}
+
+#[test]
+fn test_registry_has_marker() {
+    let source = std::fs::read_to_string(registry(&crate::day::repo_root())).unwrap();
+    assert!(source.contains(MARKER));
+}
+
+#[test]
+fn test_create() {
+    let root = scratch_dir("create");
+    std::fs::create_dir_all(registry(&root).parent().unwrap()).unwrap();
+    std::fs::write(registry(&root), format!("&[\n    {}\n]\n", MARKER)).unwrap();
+
+    let day = Day::new(2031, 6);
+    let written = create(&root, day).unwrap();
+
+    assert_eq!(written.len(), FILES.len() + 1);
+    assert!(day.exists(&root));
+
+    let makefile = std::fs::read_to_string(day.dir(&root).join("Makefile")).unwrap();
+    assert!(makefile.contains("fetch --year 2031 --day 6 --input"));
+    let main = std::fs::read_to_string(day.crate_dir(&root).join("src/main.rs")).unwrap();
+    assert!(main.contains("Solve 2031 day 6 part 1"));
+    assert!(!main.contains("{year}"));
+
+    let registry = std::fs::read_to_string(registry(&root)).unwrap();
+    assert!(registry.contains("Day::new(2031, 6),"));
+
+    // A second run must not touch anything
+    std::fs::write(day.dir(&root).join("example.txt"), "solved").unwrap();
+    assert!(matches!(create(&root, day), Err(ScaffoldError::Exists(_))));
+    let example = std::fs::read_to_string(day.dir(&root).join("example.txt")).unwrap();
+    assert_eq!(example, "solved");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_create_without_marker() {
+    let root = scratch_dir("marker");
+    std::fs::create_dir_all(registry(&root).parent().unwrap()).unwrap();
+    std::fs::write(registry(&root), "&[]\n").unwrap();
+
+    let day = Day::new(2031, 7);
+    assert!(matches!(
+        create(&root, day),
+        Err(ScaffoldError::MissingMarker(_))
+    ));
+    assert!(!day.dir(&root).exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
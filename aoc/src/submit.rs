@@ -0,0 +1,563 @@
+//! `aoc submit`: post an answer and keep track of what the site said about it.
+//!
+//! Every submission is recorded in the day's `answers.txt`. Before posting, that file is
+//! checked so answers that are known to be wrong, parts that are solved and cooldowns the
+//! site asked for are handled locally instead of by another request.
+
+use crate::day::Day;
+use crate::http::{Client, HttpError};
+use crate::puzzle;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without saying which way.
+    Incorrect,
+    /// Submitted during a cooldown; the answer was not looked at.
+    Wait,
+    /// The part was solved before, so the site did not take an answer.
+    AlreadySolved,
+    /// A page none of the above could be recognised in.
+    Unknown,
+}
+
+impl Outcome {
+    const NAMES: [(Outcome, &'static str); 7] = [
+        (Outcome::Correct, "correct"),
+        (Outcome::TooHigh, "too-high"),
+        (Outcome::TooLow, "too-low"),
+        (Outcome::Incorrect, "incorrect"),
+        (Outcome::Wait, "wait"),
+        (Outcome::AlreadySolved, "already-solved"),
+        (Outcome::Unknown, "unknown"),
+    ];
+
+    fn is_wrong(&self) -> bool {
+        matches!(
+            self,
+            Outcome::TooHigh | Outcome::TooLow | Outcome::Incorrect
+        )
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (_, name) = Self::NAMES
+            .iter()
+            .find(|(outcome, _)| outcome == self)
+            .unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(outcome, _)| *outcome)
+            .ok_or_else(|| format!("Unknown outcome {:?}", s))
+    }
+}
+
+/// What the site said about a submission.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub outcome: Outcome,
+    /// How long until the next submission is accepted.
+    pub wait: Option<Duration>,
+    /// The text of the page's message.
+    pub message: String,
+}
+
+/// Seconds in `1m 5s` or `34s`, as in "You have 1m 5s left to wait".
+fn parse_left(text: &str) -> Option<Duration> {
+    let left = text.split("You have ").nth(1)?.split(" left").next()?;
+
+    let mut secs = 0;
+    for part in left.split_whitespace() {
+        let (value, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+        let value = value.parse::<u64>().ok()?;
+        secs += match unit {
+            "h" => value * 3600,
+            "m" => value * 60,
+            "s" => value,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+/// The cooldown after a wrong answer, as in "please wait 5 minutes before trying again".
+fn parse_penalty(text: &str) -> Option<Duration> {
+    let wait = text
+        .split("wait ")
+        .nth(1)?
+        .split(" before trying again")
+        .next()?;
+    let (count, unit) = wait.split_once(' ')?;
+    let count = match count {
+        "one" => 1,
+        count => count.parse().ok()?,
+    };
+    match unit.trim_end_matches('s') {
+        "minute" => Some(Duration::from_secs(count * 60)),
+        "second" => Some(Duration::from_secs(count)),
+        _ => None,
+    }
+}
+
+impl Response {
+    pub fn parse(html: &str) -> Self {
+        let article = html
+            .split("<article>")
+            .nth(1)
+            .and_then(|article| article.split("</article>").next())
+            .unwrap_or(html);
+        let message = puzzle::text(article).trim().to_string();
+
+        let (outcome, wait) = if message.contains("That's the right answer") {
+            (Outcome::Correct, None)
+        } else if message.contains("That's not the right answer") {
+            let outcome = if message.contains("too high") {
+                Outcome::TooHigh
+            } else if message.contains("too low") {
+                Outcome::TooLow
+            } else {
+                Outcome::Incorrect
+            };
+            (outcome, parse_penalty(&message))
+        } else if message.contains("You gave an answer too recently") {
+            (Outcome::Wait, parse_left(&message))
+        } else if message.contains("You don't seem to be solving the right level") {
+            (Outcome::AlreadySolved, None)
+        } else {
+            (Outcome::Unknown, None)
+        };
+
+        Self {
+            outcome,
+            wait,
+            message,
+        }
+    }
+}
+
+/// A submission as kept in the answers file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Seconds since the Unix epoch.
+    pub at: u64,
+    pub part: u8,
+    pub answer: String,
+    pub outcome: Outcome,
+    /// No submissions until then, in seconds since the Unix epoch.
+    pub wait_until: Option<u64>,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let entry = Self {
+            at: fields.next()?.parse().ok()?,
+            part: fields.next()?.parse().ok()?,
+            answer: fields.next()?.to_string(),
+            outcome: fields.next()?.parse().ok()?,
+            wait_until: match fields.next()? {
+                "-" => None,
+                until => Some(until.parse().ok()?),
+            },
+        };
+        Some(entry)
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let wait_until = self
+            .wait_until
+            .map_or("-".to_string(), |until| until.to_string());
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.at, self.part, self.answer, self.outcome, wait_until
+        )
+    }
+}
+
+/// Why an answer was not submitted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Refusal {
+    Cooldown(Duration),
+    /// The part was solved, with the answer if it was submitted from here.
+    Solved(Option<String>),
+    /// This very answer was submitted before.
+    Repeated(Outcome),
+    /// A known wrong answer shows this one is wrong too, e.g. it is above one too high.
+    Bound {
+        outcome: Outcome,
+        known: String,
+    },
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Refusal::Cooldown(left) => {
+                write!(f, "Wait {}s before submitting again", left.as_secs())
+            }
+            Refusal::Solved(Some(answer)) => {
+                write!(f, "Already solved, the answer was {}", answer)
+            }
+            Refusal::Solved(None) => write!(f, "Already solved"),
+            Refusal::Repeated(outcome) => write!(f, "Submitted before, it was {}", outcome),
+            Refusal::Bound { outcome, known } => {
+                write!(f, "{} was {}, so this is too", known, outcome)
+            }
+        }
+    }
+}
+
+/// The day's submissions so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answers {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl Answers {
+    /// `<day>/answers.txt`; like the input it is not committed.
+    pub fn path(root: &Path, day: Day) -> PathBuf {
+        day.dir(root).join("answers.txt")
+    }
+
+    /// Read the answers file; a missing file has no entries.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: text.lines().filter_map(Entry::parse).collect(),
+        })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn append(&mut self, entry: Entry) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", entry)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Whether `answer` is worth submitting for `part` at `now`.
+    pub fn check(&self, part: u8, answer: &str, now: u64) -> Result<(), Refusal> {
+        let until = self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.wait_until)
+            .max();
+        if let Some(until) = until.filter(|&until| until > now) {
+            return Err(Refusal::Cooldown(Duration::from_secs(until - now)));
+        }
+
+        let entries = self.entries.iter().filter(|entry| entry.part == part);
+        if let Some(correct) = entries
+            .clone()
+            .find(|entry| entry.outcome == Outcome::Correct)
+        {
+            return Err(Refusal::Solved(Some(correct.answer.clone())));
+        }
+        // Solved some other way, so the answer that was sent says nothing
+        if entries
+            .clone()
+            .any(|entry| entry.outcome == Outcome::AlreadySolved)
+        {
+            return Err(Refusal::Solved(None));
+        }
+
+        for entry in entries {
+            if entry.answer == answer && entry.outcome.is_wrong() {
+                return Err(Refusal::Repeated(entry.outcome));
+            }
+
+            let bound = match (answer.parse::<i64>(), entry.answer.parse::<i64>()) {
+                (Ok(answer), Ok(known)) => match entry.outcome {
+                    Outcome::TooHigh => answer >= known,
+                    Outcome::TooLow => answer <= known,
+                    _ => false,
+                },
+                _ => false,
+            };
+            if bound {
+                return Err(Refusal::Bound {
+                    outcome: entry.outcome,
+                    known: entry.answer.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Refused(Refusal),
+    Http(HttpError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmitError::Refused(refusal) => write!(f, "Not submitted: {}", refusal),
+            SubmitError::Http(err) => write!(f, "{}", err),
+            SubmitError::Io(err) => write!(f, "Failed to record the answer: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Clock is before 1970")
+        .as_secs()
+}
+
+/// Post `answer` for `part` unless `answers` rules it out, and record what came back.
+pub fn submit(
+    client: &Client,
+    answers: &mut Answers,
+    day: Day,
+    part: u8,
+    answer: &str,
+    now: u64,
+) -> Result<Response, SubmitError> {
+    answers
+        .check(part, answer, now)
+        .map_err(SubmitError::Refused)?;
+
+    let path = format!("/{}/day/{}/answer", day.year, day.day);
+    let part_field = part.to_string();
+    let html = client
+        .post_form(&path, &[("level", &part_field), ("answer", answer)])
+        .map_err(SubmitError::Http)?;
+    let response = Response::parse(&html);
+
+    answers
+        .append(Entry {
+            at: now,
+            part,
+            answer: answer.to_string(),
+            outcome: response.outcome,
+            wait_until: response.wait.map(|wait| now + wait.as_secs()),
+        })
+        .map_err(SubmitError::Io)?;
+
+    Ok(response)
+}
+
+/// Answer pages as the site sends them, trimmed to their `<main>`.
+#[cfg(test)]
+const CORRECT: &str = "<main><article><p>That's the right answer! You are <em>one gold star</em> closer to restoring snow operations. <a href=\"/2023/day/4#part2\">[Continue to Part Two]</a></p></article></main>";
+#[cfg(test)]
+const TOO_HIGH: &str = "<main><article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; there are also some general tips on the <a href=\"/2023/about\">about page</a>, or you can ask for hints on the <a href=\"https://www.reddit.com/r/adventofcode/\" target=\"_blank\">subreddit</a>.  Please wait one minute before trying again. <a href=\"/2023/day/4\">[Return to Day 4]</a></p></article></main>";
+#[cfg(test)]
+const TOO_LOW: &str = "<main><article><p>That's not the right answer; your answer is too low.  Please wait 5 minutes before trying again. <a href=\"/2023/day/4\">[Return to Day 4]</a></p></article></main>";
+#[cfg(test)]
+const TOO_RECENT: &str = "<main><article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait. <a href=\"/2023/day/4\">[Return to Day 4]</a></p></article></main>";
+#[cfg(test)]
+const SOLVED: &str = "<main><article><p>You don't seem to be solving the right level.  Did you already complete it? <a href=\"/2023/day/4\">[Return to Day 4]</a></p></article></main>";
+
+#[test]
+fn test_parse_response() {
+    let cases = [
+        (CORRECT, Outcome::Correct, None),
+        (TOO_HIGH, Outcome::TooHigh, Some(60)),
+        (TOO_LOW, Outcome::TooLow, Some(300)),
+        (TOO_RECENT, Outcome::Wait, Some(65)),
+        (SOLVED, Outcome::AlreadySolved, None),
+        (
+            "<html>Puzzle inputs differ by user.</html>",
+            Outcome::Unknown,
+            None,
+        ),
+    ];
+
+    for (html, outcome, wait) in cases {
+        let response = Response::parse(html);
+        assert_eq!(response.outcome, outcome, "{}", html);
+        assert_eq!(response.wait, wait.map(Duration::from_secs), "{}", html);
+        assert!(!response.message.contains('<'), "{}", response.message);
+    }
+
+    assert_eq!(
+        parse_left("You have 34s left to wait."),
+        Some(Duration::from_secs(34))
+    );
+    assert_eq!(parse_left("You have 2x left to wait."), None);
+}
+
+#[test]
+fn test_entries() {
+    let entry = Entry {
+        at: 1700000000,
+        part: 2,
+        answer: "467835".to_string(),
+        outcome: Outcome::TooLow,
+        wait_until: Some(1700000060),
+    };
+    assert_eq!(Entry::parse(&entry.to_string()), Some(entry.clone()));
+
+    let entry = Entry {
+        wait_until: None,
+        outcome: Outcome::AlreadySolved,
+        ..entry
+    };
+    assert_eq!(Entry::parse(&entry.to_string()), Some(entry));
+    assert_eq!(Entry::parse("garbage"), None);
+
+    for (outcome, name) in Outcome::NAMES {
+        assert_eq!(name.parse(), Ok(outcome));
+    }
+}
+
+#[cfg(test)]
+fn answers_of(entries: &[(u8, &str, Outcome, Option<u64>)]) -> Answers {
+    Answers {
+        path: PathBuf::from("unused"),
+        entries: entries
+            .iter()
+            .map(|&(part, answer, outcome, wait_until)| Entry {
+                at: 0,
+                part,
+                answer: answer.to_string(),
+                outcome,
+                wait_until,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_check() {
+    let answers = answers_of(&[
+        (1, "100", Outcome::TooHigh, Some(60)),
+        (1, "10", Outcome::TooLow, Some(120)),
+        (1, "abc", Outcome::Incorrect, None),
+        (2, "7", Outcome::Correct, None),
+    ]);
+
+    assert_eq!(
+        answers.check(1, "50", 100),
+        Err(Refusal::Cooldown(Duration::from_secs(20)))
+    );
+    assert_eq!(answers.check(1, "50", 120), Ok(()));
+    assert_eq!(
+        answers.check(1, "abc", 120),
+        Err(Refusal::Repeated(Outcome::Incorrect))
+    );
+    assert_eq!(
+        answers.check(1, "150", 120),
+        Err(Refusal::Bound {
+            outcome: Outcome::TooHigh,
+            known: "100".to_string()
+        })
+    );
+    assert!(matches!(
+        answers.check(1, "10", 120),
+        Err(Refusal::Repeated(Outcome::TooLow))
+    ));
+    assert!(matches!(
+        answers.check(1, "5", 120),
+        Err(Refusal::Bound { .. })
+    ));
+    assert_eq!(
+        answers.check(2, "8", 120),
+        Err(Refusal::Solved(Some("7".to_string())))
+    );
+}
+
+#[test]
+fn test_check_already_solved() {
+    let answers = answers_of(&[
+        (1, "5", Outcome::AlreadySolved, None),
+        (2, "5", Outcome::AlreadySolved, None),
+        (2, "7", Outcome::Correct, None),
+    ]);
+
+    assert_eq!(answers.check(1, "6", 0), Err(Refusal::Solved(None)));
+    assert_eq!(answers.check(1, "", 0), Err(Refusal::Solved(None)));
+    assert_eq!(
+        answers.check(2, "6", 0),
+        Err(Refusal::Solved(Some("7".to_string())))
+    );
+    assert_eq!(Refusal::Solved(None).to_string(), "Already solved");
+}
+
+#[test]
+fn test_submit() {
+    let dir = std::env::temp_dir().join(format!("aoc-submit-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("answers.txt");
+    let _ = std::fs::remove_file(&path);
+
+    let (base_url, server) = crate::http::stub_server(vec![
+        (200, TOO_HIGH.to_string()),
+        (200, CORRECT.to_string()),
+    ]);
+    let client = Client::new(&base_url, Some("cookie".to_string()));
+    let day = Day::new(2023, 4);
+    let mut answers = Answers::load(&path).unwrap();
+
+    let response = submit(&client, &mut answers, day, 1, "9000", 1000).unwrap();
+    assert_eq!(response.outcome, Outcome::TooHigh);
+
+    // Refused locally: still cooling down, then known to be too high
+    assert!(matches!(
+        submit(&client, &mut answers, day, 1, "42", 1030),
+        Err(SubmitError::Refused(Refusal::Cooldown(_)))
+    ));
+    assert!(matches!(
+        submit(&client, &mut answers, day, 1, "9001", 1060),
+        Err(SubmitError::Refused(Refusal::Bound { .. }))
+    ));
+
+    let response = submit(&client, &mut answers, day, 1, "42", 1060).unwrap();
+    assert_eq!(response.outcome, Outcome::Correct);
+
+    let requests = server.join().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("POST /2023/day/4/answer HTTP/1.1"));
+    assert!(requests[0].ends_with("level=1&answer=9000"));
+    assert!(requests[1].ends_with("level=1&answer=42"));
+
+    let reloaded = Answers::load(&path).unwrap();
+    assert_eq!(reloaded, answers);
+    assert_eq!(reloaded.entries().len(), 2);
+    assert_eq!(reloaded.entries()[0].wait_until, Some(1060));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
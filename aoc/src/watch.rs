@@ -0,0 +1,506 @@
+//! `aoc watch`: re-run a day's tests and both parts whenever its files change.
+//!
+//! Changes are picked up with Linux inotify, called directly so no crates are needed.
+
+use crate::day::Day;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+mod sys {
+    use std::os::raw::{c_char, c_int, c_short, c_ulong};
+
+    pub const IN_CLOEXEC: c_int = 0o2000000;
+
+    pub const IN_CLOSE_WRITE: u32 = 0x0000_0008;
+    pub const IN_MOVED_TO: u32 = 0x0000_0080;
+    pub const IN_CREATE: u32 = 0x0000_0100;
+    pub const IN_DELETE: u32 = 0x0000_0200;
+    pub const IN_ISDIR: u32 = 0x4000_0000;
+
+    pub const POLLIN: c_short = 0x1;
+
+    #[repr(C)]
+    pub struct PollFd {
+        pub fd: c_int,
+        pub events: c_short,
+        pub revents: c_short,
+    }
+
+    extern "C" {
+        pub fn inotify_init1(flags: c_int) -> c_int;
+        pub fn inotify_add_watch(fd: c_int, path: *const c_char, mask: u32) -> c_int;
+        pub fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+    }
+}
+
+/// Size of `struct inotify_event` without its name.
+const EVENT_HEADER: usize = 16;
+
+const MASK: u32 = sys::IN_CLOSE_WRITE | sys::IN_MOVED_TO | sys::IN_CREATE | sys::IN_DELETE;
+
+/// Quiet time after a change before acting on it, so a burst of saves runs only once.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    wd: i32,
+    mask: u32,
+    name: String,
+}
+
+/// Split a buffer read from an inotify descriptor into events.
+fn parse_events(buf: &[u8]) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut at = 0;
+
+    while at + EVENT_HEADER <= buf.len() {
+        let field = |offset: usize| buf[at + offset..at + offset + 4].try_into().unwrap();
+        let wd = i32::from_ne_bytes(field(0));
+        let mask = u32::from_ne_bytes(field(4));
+        let len = u32::from_ne_bytes(field(12)) as usize;
+
+        // The name is padded with NULs
+        let name = &buf[at + EVENT_HEADER..at + EVENT_HEADER + len];
+        let name = name.split(|&b| b == 0).next().unwrap_or_default();
+
+        events.push(Event {
+            wd,
+            mask,
+            name: String::from_utf8_lossy(name).into_owned(),
+        });
+        at += EVENT_HEADER + len;
+    }
+
+    events
+}
+
+/// Whether a change to `path` can change the outcome of a run: sources, manifests and
+/// puzzle text files, but not what cargo itself writes.
+pub fn is_relevant(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+
+    name == "Cargo.toml"
+        || (!name.starts_with('.') && (name.ends_with(".rs") || name.ends_with(".txt")))
+}
+
+/// Directories watched with inotify.
+pub struct Watcher {
+    file: File,
+    dirs: HashMap<i32, PathBuf>,
+}
+
+impl Watcher {
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { sys::inotify_init1(sys::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            // The file owns the descriptor from here on and closes it when dropped
+            file: unsafe { File::from_raw_fd(fd) },
+            dirs: HashMap::new(),
+        })
+    }
+
+    /// Watch the files directly in `dir`.
+    pub fn watch_dir(&mut self, dir: &Path) -> io::Result<()> {
+        let path = CString::new(dir.as_os_str().as_bytes())?;
+        let wd = unsafe { sys::inotify_add_watch(self.file.as_raw_fd(), path.as_ptr(), MASK) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        self.dirs.insert(wd, dir.to_path_buf());
+        Ok(())
+    }
+
+    /// Watch `dir` and every directory below it, except build output.
+    pub fn watch_tree(&mut self, dir: &Path) -> io::Result<()> {
+        self.watch_dir(dir)?;
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() && !path.ends_with("target") {
+                self.watch_tree(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether events arrive within `timeout`. A signal interrupting the wait restarts it
+    /// for whatever is left of `timeout`.
+    fn poll(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            let mut fds = sys::PollFd {
+                fd: self.file.as_raw_fd(),
+                events: sys::POLLIN,
+                revents: 0,
+            };
+            // Rounded up, so the wait doesn't end just short of the deadline
+            let timeout = deadline.map_or(-1, |deadline| {
+                let left = deadline.saturating_duration_since(Instant::now());
+                left.as_micros().div_ceil(1000) as i32
+            });
+
+            match unsafe { sys::poll(&mut fds, 1, timeout) } {
+                n if n < 0 => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+                n => return Ok(n > 0),
+            }
+        }
+    }
+
+    fn read_changes(&mut self) -> io::Result<Vec<PathBuf>> {
+        let mut buf = [0; 4096];
+        let read = self.file.read(&mut buf)?;
+
+        let mut changed = Vec::new();
+        for event in parse_events(&buf[..read]) {
+            let Some(dir) = self.dirs.get(&event.wd) else {
+                continue;
+            };
+            let path = dir.join(&event.name);
+
+            if event.mask & sys::IN_ISDIR != 0 {
+                if event.mask & sys::IN_CREATE != 0 && !path.ends_with("target") {
+                    self.watch_tree(&path)?;
+                }
+            } else if is_relevant(&path) {
+                changed.push(path);
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Block until a relevant file changes, then keep collecting changes until there
+    /// have been none for `quiet`.
+    pub fn wait(&mut self, quiet: Duration) -> io::Result<Vec<PathBuf>> {
+        let mut changed = BTreeSet::new();
+
+        while changed.is_empty() {
+            self.poll(None)?;
+            changed.extend(self.read_changes()?);
+        }
+        while self.poll(Some(quiet))? {
+            changed.extend(self.read_changes()?);
+        }
+
+        Ok(changed.into_iter().collect())
+    }
+}
+
+/// Outcome of `cargo test`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestSummary {
+    /// Tests did not get to run; holds the first compiler error.
+    BuildFailed(String),
+    Ran {
+        passed: usize,
+        failed: Vec<String>,
+    },
+}
+
+impl TestSummary {
+    pub fn parse(stdout: &str, stderr: &str) -> Self {
+        let mut passed = 0;
+        let mut ran = false;
+        let mut failed = Vec::new();
+
+        for line in stdout.lines() {
+            if let Some(result) = line.strip_prefix("test result: ") {
+                ran = true;
+                passed += result
+                    .split(['.', ';'])
+                    .find_map(|part| part.trim().strip_suffix(" passed"))
+                    .and_then(|count| count.parse::<usize>().ok())
+                    .unwrap_or(0);
+            } else if let Some(name) = line
+                .strip_prefix("test ")
+                .and_then(|line| line.strip_suffix(" ... FAILED"))
+            {
+                failed.push(name.to_string());
+            }
+        }
+
+        if ran {
+            TestSummary::Ran { passed, failed }
+        } else {
+            let error = stderr
+                .lines()
+                .find(|line| line.starts_with("error"))
+                .unwrap_or("no test results");
+            TestSummary::BuildFailed(error.to_string())
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        matches!(self, TestSummary::Ran { failed, .. } if failed.is_empty())
+    }
+}
+
+impl std::fmt::Display for TestSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TestSummary::BuildFailed(error) => write!(f, "build FAILED: {}", error),
+            TestSummary::Ran { passed, failed } if failed.is_empty() => {
+                write!(f, "tests ok, {} passed", passed)
+            }
+            TestSummary::Ran { passed, failed } => write!(
+                f,
+                "tests FAILED, {} passed, {} failed: {}",
+                passed,
+                failed.len(),
+                failed.join(", ")
+            ),
+        }
+    }
+}
+
+/// Answer lines against the previous run's: unchanged lines are indented, changed ones
+/// shown as `-` old and `+` new.
+pub fn diff_answers(previous: Option<&[String]>, current: &[String]) -> Vec<String> {
+    let Some(previous) = previous else {
+        return current.iter().map(|line| format!("  {}", line)).collect();
+    };
+
+    let mut diff = Vec::new();
+    for i in 0..previous.len().max(current.len()) {
+        match (previous.get(i), current.get(i)) {
+            (Some(old), Some(new)) if old == new => diff.push(format!("  {}", new)),
+            (old, new) => {
+                diff.extend(old.map(|old| format!("- {}", old)));
+                diff.extend(new.map(|new| format!("+ {}", new)));
+            }
+        }
+    }
+    diff
+}
+
+/// The panic message out of a failed run's stderr, or else its last line.
+fn failure_reason(stderr: &[String]) -> &str {
+    let panic = stderr
+        .iter()
+        .position(|line| line.contains("panicked at"))
+        .and_then(|i| stderr.get(i + 1));
+
+    panic
+        .or_else(|| stderr.iter().rev().find(|line| !line.trim().is_empty()))
+        .map_or("", |line| line.as_str())
+}
+
+fn lines(output: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// Run the day's tests the way its Makefile does, then the day itself, and print a
+/// compact report. Returns the answer lines for the next comparison.
+pub fn run_once(root: &Path, day: Day, previous: Option<&[String]>) -> Vec<String> {
+    let tests = Command::new("cargo")
+        .args(["test", "test_", "--all-features"])
+        .current_dir(day.crate_dir(root))
+        .output()
+        .expect("Failed to run cargo test");
+    let summary = TestSummary::parse(
+        &String::from_utf8_lossy(&tests.stdout),
+        &String::from_utf8_lossy(&tests.stderr),
+    );
+    println!("{}", summary);
+
+    let run = day
+        .cargo_run(root, &[])
+        .output()
+        .expect("Failed to run cargo");
+    let answers = lines(&run.stdout);
+
+    if !run.status.success() {
+        println!("run FAILED: {}", failure_reason(&lines(&run.stderr)));
+    }
+    for line in diff_answers(previous, &answers) {
+        println!("{}", line);
+    }
+
+    answers
+}
+
+#[test]
+fn test_parse_events() {
+    let mut buf = Vec::new();
+    for (wd, mask, name) in [(1, sys::IN_CLOSE_WRITE, "main.rs"), (2, sys::IN_DELETE, "")] {
+        let padded = name.len().next_multiple_of(16);
+        buf.extend(i32::to_ne_bytes(wd));
+        buf.extend(u32::to_ne_bytes(mask));
+        buf.extend(u32::to_ne_bytes(0));
+        buf.extend(u32::to_ne_bytes(padded as u32));
+        buf.extend(name.as_bytes());
+        buf.resize(buf.len() + padded - name.len(), 0);
+    }
+
+    assert_eq!(
+        parse_events(&buf),
+        [
+            Event {
+                wd: 1,
+                mask: sys::IN_CLOSE_WRITE,
+                name: "main.rs".to_string()
+            },
+            Event {
+                wd: 2,
+                mask: sys::IN_DELETE,
+                name: String::new()
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_is_relevant() {
+    assert!(is_relevant(Path::new("/x/rust/src/main.rs")));
+    assert!(is_relevant(Path::new("/x/input.txt")));
+    assert!(is_relevant(Path::new("/x/rust/Cargo.toml")));
+    assert!(!is_relevant(Path::new("/x/rust/Cargo.lock")));
+    assert!(!is_relevant(Path::new("/x/rust/src/.main.rs.swp")));
+    assert!(!is_relevant(Path::new("/x/Makefile")));
+}
+
+#[test]
+fn test_watcher() {
+    let dir = std::env::temp_dir().join(format!("aoc-watch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+
+    let mut watcher = Watcher::new().unwrap();
+    watcher.watch_tree(&dir).unwrap();
+
+    // A burst of writes, some irrelevant, comes back as one debounced batch
+    std::fs::write(dir.join("Cargo.lock"), "").unwrap();
+    std::fs::write(dir.join("input.txt"), "1").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "").unwrap();
+    std::fs::write(dir.join("input.txt"), "2").unwrap();
+
+    let changed = watcher.wait(Duration::from_millis(50)).unwrap();
+    assert_eq!(
+        changed,
+        [dir.join("input.txt"), dir.join("src").join("main.rs")]
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_poll_retries_when_interrupted() {
+    use std::os::raw::{c_int, c_ulong};
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+        fn pthread_self() -> c_ulong;
+        fn pthread_kill(thread: c_ulong, signum: c_int) -> c_int;
+    }
+    const SIGUSR1: c_int = 10;
+    extern "C" fn ignore(_: c_int) {}
+
+    let watcher = Watcher::new().unwrap();
+    let waiting = unsafe {
+        signal(SIGUSR1, ignore);
+        pthread_self()
+    };
+    let interrupter = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(unsafe { pthread_kill(waiting, SIGUSR1) }, 0);
+    });
+
+    let start = Instant::now();
+    assert!(!watcher.poll(Some(Duration::from_millis(200))).unwrap());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    interrupter.join().unwrap();
+}
+
+#[test]
+fn test_summary() {
+    let stdout = "\
+running 3 tests
+test test_a ... ok
+test test_b ... FAILED
+test test_c ... ok
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+";
+    let summary = TestSummary::parse(stdout, "");
+    assert_eq!(
+        summary,
+        TestSummary::Ran {
+            passed: 2,
+            failed: vec!["test_b".to_string()]
+        }
+    );
+    assert!(!summary.is_ok());
+    assert_eq!(
+        summary.to_string(),
+        "tests FAILED, 2 passed, 1 failed: test_b"
+    );
+
+    let summary = TestSummary::parse("test result: ok. 5 passed; 0 failed;\n", "");
+    assert!(summary.is_ok());
+    assert_eq!(summary.to_string(), "tests ok, 5 passed");
+
+    let stderr = "   Compiling rust\nerror[E0308]: mismatched types\n";
+    assert_eq!(
+        TestSummary::parse("", stderr),
+        TestSummary::BuildFailed("error[E0308]: mismatched types".to_string())
+    );
+}
+
+#[test]
+fn test_failure_reason() {
+    let stderr = [
+        "thread 'main' panicked at src/main.rs:4:10:",
+        "Failed to read ../input.txt: No such file or directory (os error 2)",
+        "stack backtrace:",
+        "   3: rust::main",
+    ]
+    .map(String::from);
+
+    assert_eq!(
+        failure_reason(&stderr),
+        "Failed to read ../input.txt: No such file or directory (os error 2)"
+    );
+    assert_eq!(failure_reason(&stderr[2..]), "   3: rust::main");
+    assert_eq!(failure_reason(&[]), "");
+}
+
+#[test]
+fn test_diff_answers() {
+    let before = ["Part 1: 13".to_string(), "Part 2: 30".to_string()];
+    let after = ["Part 1: 13".to_string(), "Part 2: 31".to_string()];
+
+    assert_eq!(
+        diff_answers(None, &before),
+        ["  Part 1: 13", "  Part 2: 30"]
+    );
+    assert_eq!(
+        diff_answers(Some(&before), &after),
+        ["  Part 1: 13", "- Part 2: 30", "+ Part 2: 31"]
+    );
+    assert_eq!(
+        diff_answers(Some(&before), &after[..1]),
+        ["  Part 1: 13", "- Part 2: 30"]
+    );
+}